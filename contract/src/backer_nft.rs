@@ -0,0 +1,146 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    ext_contract,
+    json_types::{Base64VecU8, U128},
+    serde::{Deserialize, Serialize},
+    serde_json, AccountId, Gas, PromiseResult,
+};
+
+use crate::*;
+
+const GAS_FOR_NFT_MINT: Gas = Gas(20_000_000_000_000);
+const GAS_FOR_RESOLVE_BACKER_NFT: Gas = Gas(10_000_000_000_000);
+
+// metadata cua 1 token theo chuan NEP-177
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+// NFT contract (NEP-171) duoc owner cau hinh, contract nay phai co quyen mint
+#[allow(dead_code)]
+#[ext_contract(ext_backer_nft)]
+pub trait BackerNftContract {
+    fn nft_mint(&mut self, token_id: String, receiver_id: AccountId, token_metadata: TokenMetadata);
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BackerNftExtra {
+    id_campaign: IdCampaign,
+    tier: Option<IdTier>,
+    amount: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_backer_nft_contract(&mut self, nft_contract: Option<AccountId>) {
+        self.assert_owner();
+        self.backer_nft_contract = nft_contract;
+    }
+
+    pub fn get_backer_nft_contract(&self) -> Option<AccountId> {
+        self.backer_nft_contract.clone()
+    }
+
+    /// Backer cua campaign thanh cong nhan 1 NFT, deposit dinh kem dung de tra storage tren NFT contract
+    #[payable]
+    pub fn claim_backer_nft(&mut self, id_campaign: IdCampaign) -> Promise {
+        assert_at_least_one_yocto();
+        let nft_contract = self
+            .backer_nft_contract
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Backer NFT contract is not configured"));
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.finished && !campaign.refund,
+            "This campaign didn't succeed"
+        );
+        let backer = env::predecessor_account_id();
+        let amount = self
+            .contributors
+            .get(&id_campaign)
+            .and_then(|contributor| contributor.get(&backer))
+            .unwrap_or(0);
+        require!(amount > 0, "You never donate this campaign");
+        let key = (id_campaign, backer.clone());
+        require!(
+            !self.backer_nft_claimed.contains(&key),
+            "You already claimed the backer NFT of this campaign"
+        );
+        self.backer_nft_claimed.insert(&key);
+
+        let extra = BackerNftExtra {
+            id_campaign,
+            tier: self.contributor_tiers.get(&key),
+            amount: U128(amount),
+        };
+        let token_metadata = TokenMetadata {
+            title: Some(format!("Backer of {}", campaign.name_campaign)),
+            description: Some(format!(
+                "{} backed campaign #{} \"{}\"",
+                backer, id_campaign, campaign.name_campaign
+            )),
+            media: campaign.metadata.media.clone(),
+            issued_at: Some(env::block_timestamp_ms().to_string()),
+            extra: Some(serde_json::to_string(&extra).unwrap()),
+            ..Default::default()
+        };
+        let deposit = env::attached_deposit();
+        ext_backer_nft::ext(nft_contract)
+            .with_attached_deposit(deposit)
+            .with_static_gas(GAS_FOR_NFT_MINT)
+            .nft_mint(
+                format!("{}:{}", id_campaign, backer),
+                backer.clone(),
+                token_metadata,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_BACKER_NFT)
+                    .resolve_backer_nft(id_campaign, backer, U128(deposit)),
+            )
+    }
+
+    /// Mint that bai thi cho phep claim lai va tra lai deposit
+    #[private]
+    pub fn resolve_backer_nft(
+        &mut self,
+        id_campaign: IdCampaign,
+        backer: AccountId,
+        deposit: U128,
+    ) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return true;
+        }
+        self.backer_nft_claimed
+            .remove(&(id_campaign, backer.clone()));
+        if deposit.0 > 0 {
+            Promise::new(backer).transfer(deposit.0);
+        }
+        false
+    }
+
+    pub fn is_backer_nft_claimed(&self, id_campaign: IdCampaign, account_id: AccountId) -> bool {
+        self.backer_nft_claimed.contains(&(id_campaign, account_id))
+    }
+}
@@ -1,3 +1,8 @@
+use crate::{
+    BonusTier, CampaignMetadata, PayoutRecipient, RewardTier, RewardTierArgs, SubscriptionPlan,
+    SubscriptionPlanArgs,
+};
+use near_sdk::json_types::U64;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
@@ -8,11 +13,44 @@ use near_sdk::{
 pub struct Campaign {
     pub id: u64,
     pub name_campaign: String,
+    pub slug: String,
+    pub metadata: CampaignMetadata,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub tiers: Vec<RewardTier>,
     pub creator: AccountId,
+    pub beneficiary: AccountId,
+    // neu rong thi toan bo tien duoc tra cho beneficiary
+    pub recipients: Vec<PayoutRecipient>,
     pub goal: u128,
     pub amount: u128,
     pub time_start: u64,
     pub time_end: u64,
     pub finished: bool,
     pub refund: bool,
+    // campaign nhap, chua hien thi public va chua nhan donate
+    pub draft: bool,
+    // bonus cho nguoi donate som, rong neu khong co
+    pub bonus_tiers: Vec<BonusTier>,
+    // subscription campaign: nhan tien theo chu ky thay vi donate
+    pub subscription: Option<SubscriptionPlan>,
+    // tra dan tien cho beneficiary trong khoang thoi gian nay (ms) sau khi thanh cong
+    pub vesting_duration: Option<u64>,
+}
+
+// cac tuy chon khong bat buoc khi lunch_campaign
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(default)]
+pub struct LaunchOptions {
+    pub beneficiary: Option<AccountId>,
+    pub recipients: Option<Vec<PayoutRecipient>>,
+    pub metadata: Option<CampaignMetadata>,
+    pub draft: bool,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub tiers: Option<Vec<RewardTierArgs>>,
+    pub bonus_tiers: Option<Vec<BonusTier>>,
+    pub subscription: Option<SubscriptionPlanArgs>,
+    pub vesting_duration_ms: Option<U64>,
 }
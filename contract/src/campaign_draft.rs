@@ -0,0 +1,79 @@
+use near_sdk::json_types::U64;
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Dua campaign nhap (draft) len danh sach public
+    #[payable]
+    pub fn publish_campaign(&mut self, id_campaign: IdCampaign) {
+        assert_one_yocto();
+        let mut campaign = self.internal_get_draft(id_campaign);
+        require!(
+            env::block_timestamp_ms() < campaign.time_end,
+            "this campaign has end"
+        );
+        campaign.draft = false;
+        self.campaign.insert(&id_campaign, &campaign);
+        self.internal_list_campaign(&campaign);
+    }
+
+    #[payable]
+    pub fn update_draft_dates(&mut self, id_campaign: IdCampaign, time_start: U64, time_end: U64) {
+        assert_one_yocto();
+        let mut campaign = self.internal_get_draft(id_campaign);
+        require!(
+            time_start.0 < time_end.0,
+            "Time start must lower than Time end"
+        );
+        campaign.time_start = time_start.0;
+        campaign.time_end = time_end.0;
+        self.campaign.insert(&id_campaign, &campaign);
+    }
+
+    /// Thay toan bo reward tier cua campaign nhap, deposit tra phi storage tang them
+    #[payable]
+    pub fn update_draft_tiers(&mut self, id_campaign: IdCampaign, tiers: Vec<RewardTierArgs>) {
+        assert_at_least_one_yocto();
+        let mut campaign = self.internal_get_draft(id_campaign);
+        let init_storage = env::storage_usage();
+        campaign.tiers = build_reward_tiers(tiers);
+        self.campaign.insert(&id_campaign, &campaign);
+        refund_deposit(env::storage_usage().saturating_sub(init_storage));
+    }
+
+    /// Xoa campaign nhap va tra lai phi storage cho creator
+    #[payable]
+    pub fn delete_draft_campaign(&mut self, id_campaign: IdCampaign) {
+        assert_one_yocto();
+        let campaign = self.internal_get_draft(id_campaign);
+        let init_storage = env::storage_usage();
+        self.internal_close_sponsor_pools(id_campaign, false);
+        self.campaign.remove(&id_campaign);
+        self.internal_remove_campaign_from_creator(&campaign.creator, id_campaign);
+        self.internal_clear_campaign_managers(id_campaign);
+        self.pending_owner.remove(&id_campaign);
+        self.campaign_edits.remove(&id_campaign);
+        self.slugs.remove(&campaign.slug);
+        let storage_freed = init_storage.saturating_sub(env::storage_usage());
+        let refund = env::storage_byte_cost() * storage_freed as u128;
+        if refund > 0 {
+            Promise::new(campaign.creator).transfer(refund);
+        }
+    }
+}
+
+impl Contract {
+    fn internal_get_draft(&self, id_campaign: IdCampaign) -> Campaign {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        require!(campaign.draft, "This campaign is not a draft");
+        campaign
+    }
+}
@@ -0,0 +1,132 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+// thoi gian toi da tu time_start den time_end khi gia han (365 ngay)
+const MAX_CAMPAIGN_DURATION_MS: u64 = 365 * 24 * 60 * 60 * 1000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum CampaignChange {
+    Metadata { previous: CampaignMetadata },
+    Deadline { previous: u64, new: u64 },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CampaignEdit {
+    pub editor: AccountId,
+    pub time_edit: u64,
+    pub change: CampaignChange,
+}
+
+impl Contract {
+    fn internal_record_edit(&mut self, id_campaign: IdCampaign, change: CampaignChange) {
+        let mut edits = self
+            .campaign_edits
+            .get(&id_campaign)
+            .unwrap_or_else(|| Vector::new(StorageKey::CampaignEditsNested { id_campaign }));
+        edits.push(&CampaignEdit {
+            editor: env::predecessor_account_id(),
+            time_edit: env::block_timestamp_ms(),
+            change,
+        });
+        self.campaign_edits.insert(&id_campaign, &edits);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn update_campaign_metadata(
+        &mut self,
+        id_campaign: IdCampaign,
+        metadata: CampaignMetadata,
+    ) {
+        assert_at_least_one_yocto();
+        let init_storage = env::storage_usage();
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            self.has_campaign_permission(
+                &campaign,
+                &env::predecessor_account_id(),
+                Permission::EditMetadata
+            ),
+            "You are not allowed to edit this campaign"
+        );
+        metadata.assert_valid();
+        let previous = std::mem::replace(&mut campaign.metadata, metadata);
+        self.campaign.insert(&id_campaign, &campaign);
+        self.internal_record_edit(id_campaign, CampaignChange::Metadata { previous });
+        refund_deposit(env::storage_usage().saturating_sub(init_storage));
+    }
+
+    /// Chi duoc keo dai time_end khi campaign dang chay, khong duoc rut ngan
+    #[payable]
+    pub fn extend_campaign_deadline(&mut self, id_campaign: IdCampaign, time_end: U64) {
+        assert_at_least_one_yocto();
+        let init_storage = env::storage_usage();
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            self.has_campaign_permission(
+                &campaign,
+                &env::predecessor_account_id(),
+                Permission::EditMetadata
+            ),
+            "You are not allowed to edit this campaign"
+        );
+        require!(
+            !campaign.draft && !campaign.finished && env::block_timestamp_ms() <= campaign.time_end,
+            "this campaign has end"
+        );
+        let time_end = time_end.0;
+        require!(
+            time_end > campaign.time_end,
+            "New time end must be later than the current one"
+        );
+        require!(
+            time_end - campaign.time_start <= MAX_CAMPAIGN_DURATION_MS,
+            "Campaign duration exceeds the maximum"
+        );
+        let previous = campaign.time_end;
+        self.deadline_index.remove(&(previous, id_campaign));
+        self.deadline_index.insert(&(time_end, id_campaign), &());
+        campaign.time_end = time_end;
+        self.campaign.insert(&id_campaign, &campaign);
+        self.internal_record_edit(
+            id_campaign,
+            CampaignChange::Deadline {
+                previous,
+                new: time_end,
+            },
+        );
+        refund_deposit(env::storage_usage().saturating_sub(init_storage));
+    }
+
+    pub fn get_campaign_edits(
+        &self,
+        id_campaign: IdCampaign,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<CampaignEdit> {
+        match self.campaign_edits.get(&id_campaign) {
+            Some(edits) => edits
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .collect(),
+            None => vec![],
+        }
+    }
+}
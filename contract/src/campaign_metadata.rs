@@ -0,0 +1,59 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::Base64VecU8,
+    require,
+    serde::{Deserialize, Serialize},
+};
+
+const MAX_DESCRIPTION_LEN: usize = 2000;
+const MAX_URL_LEN: usize = 512;
+const MAX_LINKS: usize = 5;
+const HASH_LEN: usize = 32;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(default)]
+pub struct CampaignMetadata {
+    pub description: String,
+    pub media: Option<String>,               // URL anh cover
+    pub media_hash: Option<Base64VecU8>,     // sha256 cua noi dung media
+    pub links: Vec<String>,                  // website, mang xa hoi, ...
+    pub reference: Option<String>,           // URL toi file JSON (IPFS) chua thong tin them
+    pub reference_hash: Option<Base64VecU8>, // sha256 cua file reference
+}
+
+impl CampaignMetadata {
+    pub fn assert_valid(&self) {
+        require!(
+            self.description.len() <= MAX_DESCRIPTION_LEN,
+            "Description is too long"
+        );
+        require!(self.links.len() <= MAX_LINKS, "Too many links");
+        require!(
+            self.links.iter().all(|link| link.len() <= MAX_URL_LEN),
+            "Link is too long"
+        );
+        require!(
+            self.media.as_ref().map_or(0, |m| m.len()) <= MAX_URL_LEN,
+            "Media URL is too long"
+        );
+        require!(
+            self.reference.as_ref().map_or(0, |r| r.len()) <= MAX_URL_LEN,
+            "Reference URL is too long"
+        );
+        require!(
+            self.media.is_some() == self.media_hash.is_some(),
+            "Media and media hash must be present together"
+        );
+        require!(
+            self.reference.is_some() == self.reference_hash.is_some(),
+            "Reference and reference hash must be present together"
+        );
+        for hash in [&self.media_hash, &self.reference_hash]
+            .into_iter()
+            .flatten()
+        {
+            require!(hash.0.len() == HASH_LEN, "Hash has to be 32 bytes");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use near_sdk::collections::UnorderedSet;
+
+use crate::*;
+
+// cac category co san, owner co the them category moi qua add_category
+const DEFAULT_CATEGORIES: [&str; 8] = [
+    "technology",
+    "art",
+    "charity",
+    "education",
+    "health",
+    "community",
+    "games",
+    "environment",
+];
+const MAX_TAGS: usize = 5;
+const MAX_TAG_LEN: usize = 32;
+
+/// Chuan hoa va kiem tra tags truoc khi luu vao campaign
+pub(crate) fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    require!(tags.len() <= MAX_TAGS, "Too many tags");
+    let mut result: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_slug(&tag);
+        require!(
+            !tag.is_empty() && tag.len() <= MAX_TAG_LEN,
+            "Tag must have between 1 and 32 characters"
+        );
+        if !result.contains(&tag) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+impl Contract {
+    pub(crate) fn is_category(&self, category: &String) -> bool {
+        DEFAULT_CATEGORIES.contains(&category.as_str()) || self.extra_categories.contains(category)
+    }
+
+    /// Them campaign vao index category/tag khi campaign duoc public
+    pub(crate) fn internal_index_category_tags(&mut self, campaign: &Campaign) {
+        if let Some(category) = &campaign.category {
+            let mut ids = self.campaigns_by_category.get(category).unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::CampaignsByCategoryNested {
+                    category_hash: env::sha256(category.as_bytes()),
+                })
+            });
+            ids.insert(&campaign.id);
+            self.campaigns_by_category.insert(category, &ids);
+        }
+        for tag in campaign.tags.iter() {
+            let mut ids = self.campaigns_by_tag.get(tag).unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::CampaignsByTagNested {
+                    tag_hash: env::sha256(tag.as_bytes()),
+                })
+            });
+            ids.insert(&campaign.id);
+            self.campaigns_by_tag.insert(tag, &ids);
+        }
+    }
+
+    pub(crate) fn internal_unindex_category_tags(&mut self, campaign: &Campaign) {
+        if let Some(category) = &campaign.category {
+            if let Some(mut ids) = self.campaigns_by_category.get(category) {
+                ids.remove(&campaign.id);
+                self.campaigns_by_category.insert(category, &ids);
+            }
+        }
+        for tag in campaign.tags.iter() {
+            if let Some(mut ids) = self.campaigns_by_tag.get(tag) {
+                ids.remove(&campaign.id);
+                self.campaigns_by_tag.insert(tag, &ids);
+            }
+        }
+    }
+
+    fn internal_campaigns_in(
+        &self,
+        ids: Option<UnorderedSet<IdCampaign>>,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Campaign> {
+        match ids {
+            Some(ids) => ids
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .filter_map(|id| self.campaign.get(&id))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn add_category(&mut self, category: String) {
+        self.assert_owner();
+        let category = normalize_slug(&category);
+        require!(!category.is_empty(), "Category must not be empty");
+        require!(!self.is_category(&category), "Category already exists");
+        self.extra_categories.insert(&category);
+    }
+
+    pub fn get_categories(&self) -> Vec<String> {
+        DEFAULT_CATEGORIES
+            .iter()
+            .map(|c| c.to_string())
+            .chain(self.extra_categories.iter())
+            .collect()
+    }
+
+    pub fn get_campaigns_by_category(
+        &self,
+        category: String,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Campaign> {
+        self.internal_campaigns_in(
+            self.campaigns_by_category.get(&normalize_slug(&category)),
+            from_index,
+            limit,
+        )
+    }
+
+    pub fn get_campaigns_by_tag(
+        &self,
+        tag: String,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Campaign> {
+        self.internal_campaigns_in(
+            self.campaigns_by_tag.get(&normalize_slug(&tag)),
+            from_index,
+            limit,
+        )
+    }
+}
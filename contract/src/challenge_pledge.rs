@@ -0,0 +1,196 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+const MAX_CHALLENGE_PLEDGES: usize = 50;
+// pledge toi thieu 0.1 NEAR, tranh spam pledge nho chiem het MAX_CHALLENGE_PLEDGES
+const MIN_CHALLENGE_PLEDGE: u128 = 10u128.pow(23);
+
+// "toi donate amount neu campaign nhan duoc threshold tu nhung nguoi khac"
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChallengePledge {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub threshold: U128,
+}
+
+impl Contract {
+    /// Danh gia cac pledge khi ket thuc campaign: pledge dat dieu kien duoc cong vao
+    /// amount va contributors, con lai tra tien cho nguoi pledge
+    pub(crate) fn internal_settle_challenge_pledges(&mut self, campaign: &mut Campaign) {
+        let id_campaign = campaign.id;
+        let pledges = match self.challenge_pledges.remove(&id_campaign) {
+            Some(pledges) => pledges,
+            None => return,
+        };
+        let mut contributor = self
+            .contributors
+            .get(&id_campaign)
+            .unwrap_or_else(|| LookupMap::new(StorageKey::ContributorsNested { id_campaign }));
+        let own: Vec<u128> = pledges
+            .iter()
+            .map(|p| contributor.get(&p.account_id).unwrap_or(0))
+            .collect();
+        // pledge dat dieu kien lam tang so tien cua nguoi khac, lap den khi on dinh
+        let mut met = vec![false; pledges.len()];
+        let mut pledged = 0u128;
+        loop {
+            let mut changed = false;
+            for (i, pledge) in pledges.iter().enumerate() {
+                if met[i] {
+                    continue;
+                }
+                // pledge da dat cua chinh account nay khong tinh la tien cua nguoi khac
+                let own_met: u128 = pledges
+                    .iter()
+                    .zip(met.iter())
+                    .filter(|(p, met)| **met && p.account_id == pledge.account_id)
+                    .map(|(p, _)| p.amount.0)
+                    .sum();
+                let others = campaign.amount + pledged - own[i] - own_met;
+                if others >= pledge.threshold.0 {
+                    met[i] = true;
+                    pledged += pledge.amount.0;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        for (pledge, met) in pledges.into_iter().zip(met) {
+            if !met {
+                self.internal_payout(
+                    id_campaign,
+                    pledge.account_id,
+                    pledge.amount.0,
+                    PayoutKind::Pledge,
+                );
+                continue;
+            }
+            self.internal_snapshot_yield_principal(
+                id_campaign,
+                &pledge.account_id,
+                contributor.get(&pledge.account_id).unwrap_or(0),
+            );
+            self.internal_add_weighted(id_campaign, &pledge.account_id, pledge.amount.0);
+            match contributor.get(&pledge.account_id) {
+                Some(money) => {
+                    contributor.insert(&pledge.account_id, &(money + pledge.amount.0));
+                }
+                None => {
+                    contributor.insert(&pledge.account_id, &pledge.amount.0);
+                    let mut donors = self
+                        .donors
+                        .get(&id_campaign)
+                        .unwrap_or_else(|| Vector::new(StorageKey::DonorsNested { id_campaign }));
+                    donors.push(&pledge.account_id);
+                    self.donors.insert(&id_campaign, &donors);
+                }
+            }
+        }
+        self.contributors.insert(&id_campaign, &contributor);
+        campaign.amount = campaign
+            .amount
+            .checked_add(pledged)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+    }
+
+    /// Tra lai toan bo pledge (vd: campaign bi huy)
+    pub(crate) fn internal_refund_challenge_pledges(&mut self, id_campaign: IdCampaign) {
+        for pledge in self
+            .challenge_pledges
+            .remove(&id_campaign)
+            .unwrap_or_default()
+        {
+            self.internal_payout(
+                id_campaign,
+                pledge.account_id,
+                pledge.amount.0,
+                PayoutKind::Pledge,
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deposit dinh kem chi duoc donate neu campaign nhan du threshold tu nguoi khac
+    #[payable]
+    pub fn pledge_challenge(&mut self, id_campaign: IdCampaign, threshold: U128) {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(!campaign.draft, "This campaign is a draft");
+        require!(!campaign.finished, "This campaign was finished");
+        require!(
+            campaign.subscription.is_none(),
+            "Subscription campaigns only accept subscribe"
+        );
+        require!(
+            env::block_timestamp_ms() >= campaign.time_start,
+            "This campaign not start yet"
+        );
+        require!(
+            env::block_timestamp_ms() <= campaign.time_end,
+            "this campaign has end"
+        );
+        let amount = env::attached_deposit();
+        require!(
+            amount >= MIN_CHALLENGE_PLEDGE,
+            "Pledge must be at least 0.1 NEAR"
+        );
+        let mut pledges = self.challenge_pledges.get(&id_campaign).unwrap_or_default();
+        require!(
+            pledges.len() < MAX_CHALLENGE_PLEDGES,
+            "Too many challenge pledges for this campaign"
+        );
+        pledges.push(ChallengePledge {
+            account_id: env::predecessor_account_id(),
+            amount: U128(amount),
+            threshold,
+        });
+        self.challenge_pledges.insert(&id_campaign, &pledges);
+    }
+
+    /// Rut lai cac pledge cua caller truoc khi campaign ket thuc
+    #[payable]
+    pub fn cancel_challenge_pledges(&mut self, id_campaign: IdCampaign) -> U128 {
+        assert_one_yocto();
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            env::block_timestamp_ms() <= campaign.time_end,
+            "this campaign has end"
+        );
+        let account_id = env::predecessor_account_id();
+        let mut pledges = self.challenge_pledges.get(&id_campaign).unwrap_or_default();
+        let total: u128 = pledges
+            .iter()
+            .filter(|p| p.account_id == account_id)
+            .map(|p| p.amount.0)
+            .sum();
+        require!(total > 0, "You have no challenge pledge for this campaign");
+        pledges.retain(|p| p.account_id != account_id);
+        if pledges.is_empty() {
+            self.challenge_pledges.remove(&id_campaign);
+        } else {
+            self.challenge_pledges.insert(&id_campaign, &pledges);
+        }
+        self.internal_payout(id_campaign, account_id, total, PayoutKind::Pledge);
+        U128(total)
+    }
+
+    pub fn get_challenge_pledges(&self, id_campaign: IdCampaign) -> Vec<ChallengePledge> {
+        self.challenge_pledges.get(&id_campaign).unwrap_or_default()
+    }
+}
@@ -0,0 +1,157 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+const MAX_BONUS_TIERS: usize = 5;
+// bonus toi da +100%
+const MAX_BONUS_BPS: u16 = 10_000;
+
+// donate trong until_bps dau tien cua [time_start, time_end] duoc cong them bonus_bps
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BonusTier {
+    pub until_bps: u16,
+    pub bonus_bps: u16,
+}
+
+pub(crate) fn assert_valid_bonus_tiers(tiers: &[BonusTier]) {
+    require!(tiers.len() <= MAX_BONUS_TIERS, "Too many bonus tiers");
+    let mut previous = 0;
+    for tier in tiers {
+        require!(
+            tier.until_bps > previous && tier.until_bps <= TOTAL_SHARE_BPS,
+            "Bonus tiers must be sorted and end within 10000 basis points"
+        );
+        require!(
+            tier.bonus_bps > 0 && tier.bonus_bps <= MAX_BONUS_BPS,
+            "Bonus must be between 1 and 10000 basis points"
+        );
+        previous = tier.until_bps;
+    }
+}
+
+/// Bonus ap dung cho donate tai thoi diem now (ms)
+pub(crate) fn bonus_bps_at(campaign: &Campaign, now: u64) -> u16 {
+    let elapsed = now.saturating_sub(campaign.time_start) as u128;
+    let duration = (campaign.time_end - campaign.time_start) as u128;
+    let elapsed_bps = elapsed * TOTAL_SHARE_BPS as u128 / duration;
+    campaign
+        .bonus_tiers
+        .iter()
+        .find(|tier| elapsed_bps < tier.until_bps as u128)
+        .map_or(0, |tier| tier.bonus_bps)
+}
+
+pub(crate) fn weighted_amount(amount: u128, bonus_bps: u16) -> u128 {
+    let total = TOTAL_SHARE_BPS as u128;
+    (U256::from(amount) * U256::from(total + bonus_bps as u128) / U256::from(total)).as_u128()
+}
+
+impl Contract {
+    /// So tien da nhan trong so, mac dinh bang so tien donate
+    pub(crate) fn internal_weighted_contribution(
+        &self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+    ) -> u128 {
+        self.weighted_contributors
+            .get(&(id_campaign, account_id.clone()))
+            .unwrap_or_else(|| {
+                self.contributors
+                    .get(&id_campaign)
+                    .and_then(|contributor| contributor.get(account_id))
+                    .unwrap_or(0)
+            })
+    }
+
+    /// Cong them weighted, tra ve tong weighted moi cua contributor
+    pub(crate) fn internal_add_weighted(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        weighted: u128,
+    ) -> u128 {
+        let key = (id_campaign, account_id.clone());
+        let total = self
+            .weighted_contributors
+            .get(&key)
+            .unwrap_or(0)
+            .checked_add(weighted)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        self.weighted_contributors.insert(&key, &total);
+        total
+    }
+
+    /// Giam weighted theo ti le refund / donated, tra ve (phan bi tru, phan con lai)
+    pub(crate) fn internal_release_weighted(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        refund: u128,
+        donated: u128,
+    ) -> (u128, u128) {
+        let weighted = self.internal_weighted_contribution(id_campaign, account_id);
+        let released = if refund >= donated {
+            weighted
+        } else {
+            (U256::from(weighted) * U256::from(refund) / U256::from(donated)).as_u128()
+        };
+        let key = (id_campaign, account_id.clone());
+        if released == weighted {
+            self.weighted_contributors.remove(&key);
+        } else {
+            self.weighted_contributors
+                .insert(&key, &(weighted - released));
+        }
+        (released, weighted - released)
+    }
+
+    /// Xoa weighted khi contributor duoc refund toan bo
+    pub(crate) fn internal_remove_weighted(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        donated: u128,
+    ) -> u128 {
+        self.weighted_contributors
+            .remove(&(id_campaign, account_id.clone()))
+            .unwrap_or(donated)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creator chi doi duoc bonus tier truoc khi campaign bat dau nhan donate
+    #[payable]
+    pub fn set_bonus_tiers(&mut self, id_campaign: IdCampaign, bonus_tiers: Vec<BonusTier>) {
+        assert_one_yocto();
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        require!(
+            campaign.draft || env::block_timestamp_ms() < campaign.time_start,
+            "Bonus tiers can't be changed after the campaign started"
+        );
+        assert_valid_bonus_tiers(&bonus_tiers);
+        campaign.bonus_tiers = bonus_tiers;
+        self.campaign.insert(&id_campaign, &campaign);
+    }
+
+    pub fn get_weighted_contribution(
+        &self,
+        id_campaign: IdCampaign,
+        account_id: AccountId,
+    ) -> U128 {
+        U128(self.internal_weighted_contribution(id_campaign, &account_id))
+    }
+}
@@ -0,0 +1,486 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    ext_contract,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    serde_json, AccountId, Gas, PromiseOrValue, PromiseResult,
+};
+
+use crate::*;
+
+const GAS_FOR_DEPOSIT_AND_STAKE: Gas = Gas(50_000_000_000_000);
+const GAS_FOR_GET_BALANCE: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_UNSTAKE: Gas = Gas(50_000_000_000_000);
+const GAS_FOR_WITHDRAW: Gas = Gas(50_000_000_000_000);
+const GAS_FOR_RESOLVE_STAKING: Gas = Gas(10_000_000_000_000);
+// callback sau get_account_total_balance con phai goi unstake/deposit_and_stake + callback
+const GAS_FOR_RESOLVE_BALANCE: Gas = Gas(80_000_000_000_000);
+
+// interface cua staking pool chuan (core-contracts/staking-pool)
+#[allow(dead_code)]
+#[ext_contract(ext_staking_pool)]
+pub trait StakingPool {
+    fn deposit_and_stake(&mut self);
+    fn unstake(&mut self, amount: U128);
+    fn withdraw(&mut self, amount: U128);
+    fn get_account_total_balance(&self, account_id: AccountId) -> U128;
+}
+
+// loi nhuan staking duoc chuyen cho ai
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum YieldDestination {
+    // cong vao tien match cua campaign, tra luc finished_campaign
+    Campaign,
+    // donor tu claim theo ti le so tien donate
+    Donors,
+    Treasury,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EscrowStatus {
+    Staking,
+    Staked,
+    Unstaking,
+    Unstaked,
+    Withdrawing,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowStake {
+    pub pool: AccountId,
+    pub principal: U128,
+    // phan so huu tren pool, mint theo gia balance / tong shares luc stake
+    pub shares: U128,
+    // principal + loi nhuan, co khi da unstake
+    pub unstaked: U128,
+    pub status: EscrowStatus,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DonorYield {
+    pub total: U128,
+    pub principal: U128,
+    pub claimed: U128,
+}
+
+impl Contract {
+    /// Tien cua campaign dang nam trong staking pool thi khong the tra ra
+    pub(crate) fn assert_escrow_not_staked(&self, id_campaign: IdCampaign) {
+        require!(
+            self.escrow_stakes.get(&id_campaign).is_none(),
+            "Escrow of this campaign is staked"
+        );
+    }
+
+    fn internal_set_escrow_status(&mut self, id_campaign: IdCampaign, status: EscrowStatus) {
+        let mut stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        stake.status = status;
+        self.escrow_stakes.insert(&id_campaign, &stake);
+    }
+
+    fn internal_update_pool_shares(&mut self, pool: &AccountId, added: u128, removed: u128) {
+        let shares = self.pool_shares.get(pool).unwrap_or(0) + added - removed;
+        if shares == 0 {
+            self.pool_shares.remove(pool);
+        } else {
+            self.pool_shares.insert(pool, &shares);
+        }
+    }
+
+    /// Moi luc chi 1 thao tac doc/thay doi so du tren pool, tranh tinh gia shares sai
+    fn internal_lock_pool(&mut self, pool: &AccountId) {
+        require!(
+            self.pool_locked.insert(pool),
+            "This staking pool is busy, try again later"
+        );
+    }
+
+    /// So du tren pool thuoc ve cac campaign dang stake
+    fn internal_pool_available(&self, pool: &AccountId, balance: u128) -> u128 {
+        balance.saturating_sub(self.pool_unstaked.get(pool).unwrap_or(0))
+    }
+
+    fn internal_update_pool_unstaked(&mut self, pool: &AccountId, added: u128, removed: u128) {
+        let unstaked = (self.pool_unstaked.get(pool).unwrap_or(0) + added).saturating_sub(removed);
+        if unstaked == 0 {
+            self.pool_unstaked.remove(pool);
+        } else {
+            self.pool_unstaked.insert(pool, &unstaked);
+        }
+    }
+
+    /// Luu so tien donor da donate truoc khi thay doi, trong luc escrow dang stake hoac
+    /// loi nhuan chua claim, de chia loi nhuan theo so tien luc stake
+    pub(crate) fn internal_snapshot_yield_principal(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        donated: u128,
+    ) {
+        if self.escrow_stakes.get(&id_campaign).is_none()
+            && self.donor_yields.get(&id_campaign).is_none()
+        {
+            return;
+        }
+        let key = (id_campaign, account_id.clone());
+        if self.yield_claimed.contains(&key) || self.yield_snapshots.get(&key).is_some() {
+            return;
+        }
+        self.yield_snapshots.insert(&key, &donated);
+    }
+
+    fn internal_distribute_yield(
+        &mut self,
+        id_campaign: IdCampaign,
+        principal: u128,
+        amount: u128,
+    ) {
+        match self.yield_destination {
+            YieldDestination::Campaign => {
+                let matched = self.campaign_matched.get(&id_campaign).unwrap_or(0);
+                self.campaign_matched
+                    .insert(&id_campaign, &(matched + amount));
+            }
+            YieldDestination::Donors => {
+                self.donor_yields.insert(
+                    &id_campaign,
+                    &DonorYield {
+                        total: U128(amount),
+                        principal: U128(principal),
+                        claimed: U128(0),
+                    },
+                );
+            }
+            YieldDestination::Treasury => {
+                Promise::new(self.owner_id.clone()).transfer(amount);
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn add_staking_pool(&mut self, pool: AccountId) {
+        self.assert_owner();
+        self.staking_pools.insert(&pool);
+    }
+
+    pub fn remove_staking_pool(&mut self, pool: AccountId) {
+        self.assert_owner();
+        self.staking_pools.remove(&pool);
+    }
+
+    pub fn get_staking_pools(&self) -> Vec<AccountId> {
+        self.staking_pools.to_vec()
+    }
+
+    pub fn set_yield_destination(&mut self, destination: YieldDestination) {
+        self.assert_owner();
+        self.yield_destination = destination;
+    }
+
+    pub fn get_yield_destination(&self) -> YieldDestination {
+        self.yield_destination
+    }
+
+    /// Creator stake toan bo tien dang giu cua campaign vao pool da duoc whitelist
+    pub fn stake_escrow(&mut self, id_campaign: IdCampaign, pool: AccountId) -> Promise {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            self.has_campaign_permission(
+                &campaign,
+                &env::predecessor_account_id(),
+                Permission::Finalize
+            ),
+            "You are not the creator of this campaign"
+        );
+        require!(!campaign.finished, "This campaign was finished");
+        require!(
+            self.staking_pools.contains(&pool),
+            "This staking pool is not whitelisted"
+        );
+        self.assert_escrow_not_staked(id_campaign);
+        require!(campaign.amount > 0, "This campaign has nothing to stake");
+        self.internal_lock_pool(&pool);
+        self.escrow_stakes.insert(
+            &id_campaign,
+            &EscrowStake {
+                pool: pool.clone(),
+                principal: U128(campaign.amount),
+                shares: U128(0),
+                unstaked: U128(0),
+                status: EscrowStatus::Staking,
+            },
+        );
+        ext_staking_pool::ext(pool)
+            .with_static_gas(GAS_FOR_GET_BALANCE)
+            .get_account_total_balance(env::current_account_id())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_BALANCE)
+                    .on_escrow_stake_balance(id_campaign),
+            )
+    }
+
+    /// Mint shares theo gia hien tai cua pool roi moi deposit
+    #[private]
+    pub fn on_escrow_stake_balance(&mut self, id_campaign: IdCampaign) -> PromiseOrValue<bool> {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        let mut stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        let balance = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value).ok(),
+            _ => None,
+        };
+        let balance = match balance {
+            Some(balance) => balance.0,
+            None => {
+                self.escrow_stakes.remove(&id_campaign);
+                self.pool_locked.remove(&stake.pool);
+                return PromiseOrValue::Value(false);
+            }
+        };
+        let total_shares = self.pool_shares.get(&stake.pool).unwrap_or(0);
+        let available = self.internal_pool_available(&stake.pool, balance);
+        let shares = if total_shares == 0 || available == 0 {
+            stake.principal.0
+        } else {
+            (U256::from(stake.principal.0) * U256::from(total_shares) / U256::from(available))
+                .as_u128()
+        };
+        stake.shares = U128(shares);
+        self.escrow_stakes.insert(&id_campaign, &stake);
+        PromiseOrValue::Promise(
+            ext_staking_pool::ext(stake.pool)
+                .with_attached_deposit(stake.principal.0)
+                .with_static_gas(GAS_FOR_DEPOSIT_AND_STAKE)
+                .deposit_and_stake()
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_RESOLVE_STAKING)
+                        .on_escrow_staked(id_campaign),
+                ),
+        )
+    }
+
+    /// Stake that bai thi tien da quay ve contract
+    #[private]
+    pub fn on_escrow_staked(&mut self, id_campaign: IdCampaign) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        let mut stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        self.pool_locked.remove(&stake.pool);
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.internal_update_pool_shares(&stake.pool, stake.shares.0, 0);
+            stake.status = EscrowStatus::Staked;
+            self.escrow_stakes.insert(&id_campaign, &stake);
+            return true;
+        }
+        self.escrow_stakes.remove(&id_campaign);
+        false
+    }
+
+    /// Creator unstake bat ky luc nao, sau time_end thi ai cung goi duoc
+    pub fn unstake_escrow(&mut self, id_campaign: IdCampaign) -> Promise {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            env::block_timestamp_ms() >= campaign.time_end
+                || self.has_campaign_permission(
+                    &campaign,
+                    &env::predecessor_account_id(),
+                    Permission::Finalize
+                ),
+            "The time of this campaign is not over yet"
+        );
+        let stake = self
+            .escrow_stakes
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("Escrow of this campaign is not staked"));
+        require!(
+            stake.status == EscrowStatus::Staked,
+            "Escrow of this campaign is not staked"
+        );
+        self.internal_lock_pool(&stake.pool);
+        self.internal_set_escrow_status(id_campaign, EscrowStatus::Unstaking);
+        ext_staking_pool::ext(stake.pool)
+            .with_static_gas(GAS_FOR_GET_BALANCE)
+            .get_account_total_balance(env::current_account_id())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_BALANCE)
+                    .on_escrow_balance(id_campaign),
+            )
+    }
+
+    /// Quy doi shares cua campaign ra so tien theo gia hien tai cua pool
+    #[private]
+    pub fn on_escrow_balance(&mut self, id_campaign: IdCampaign) -> PromiseOrValue<bool> {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        let mut stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        let balance = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value).ok(),
+            _ => None,
+        };
+        let balance = match balance {
+            Some(balance) => balance.0,
+            None => {
+                stake.status = EscrowStatus::Staked;
+                self.escrow_stakes.insert(&id_campaign, &stake);
+                self.pool_locked.remove(&stake.pool);
+                return PromiseOrValue::Value(false);
+            }
+        };
+        let total_shares = self.pool_shares.get(&stake.pool).unwrap();
+        // tien cac campaign khac da unstake nhung chua withdraw khong thuoc ve shares
+        let available = self.internal_pool_available(&stake.pool, balance);
+        let amount = (U256::from(stake.shares.0) * U256::from(available)
+            / U256::from(total_shares))
+        .as_u128();
+        stake.unstaked = U128(amount);
+        self.escrow_stakes.insert(&id_campaign, &stake);
+        self.internal_update_pool_shares(&stake.pool, 0, stake.shares.0);
+        self.internal_update_pool_unstaked(&stake.pool, amount, 0);
+        PromiseOrValue::Promise(
+            ext_staking_pool::ext(stake.pool)
+                .with_static_gas(GAS_FOR_UNSTAKE)
+                .unstake(U128(amount))
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_RESOLVE_STAKING)
+                        .on_escrow_unstaked(id_campaign),
+                ),
+        )
+    }
+
+    #[private]
+    pub fn on_escrow_unstaked(&mut self, id_campaign: IdCampaign) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        let mut stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        self.pool_locked.remove(&stake.pool);
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            stake.status = EscrowStatus::Unstaked;
+            self.escrow_stakes.insert(&id_campaign, &stake);
+            return true;
+        }
+        stake.status = EscrowStatus::Staked;
+        self.internal_update_pool_unstaked(&stake.pool, 0, stake.unstaked.0);
+        stake.unstaked = U128(0);
+        self.internal_update_pool_shares(&stake.pool, stake.shares.0, 0);
+        self.escrow_stakes.insert(&id_campaign, &stake);
+        false
+    }
+
+    /// Rut tien ve contract, pool chi cho rut sau vai epoch ke tu luc unstake
+    pub fn withdraw_escrow(&mut self, id_campaign: IdCampaign) -> Promise {
+        let stake = self
+            .escrow_stakes
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("Escrow of this campaign is not staked"));
+        require!(
+            stake.status == EscrowStatus::Unstaked,
+            "Escrow of this campaign is not unstaked yet"
+        );
+        self.internal_lock_pool(&stake.pool);
+        self.internal_set_escrow_status(id_campaign, EscrowStatus::Withdrawing);
+        ext_staking_pool::ext(stake.pool)
+            .with_static_gas(GAS_FOR_WITHDRAW)
+            .withdraw(stake.unstaked)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_STAKING)
+                    .on_escrow_withdrawn(id_campaign),
+            )
+    }
+
+    /// Tien da ve contract: chia loi nhuan theo yield_destination
+    #[private]
+    pub fn on_escrow_withdrawn(&mut self, id_campaign: IdCampaign) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        let stake = self.escrow_stakes.get(&id_campaign).unwrap();
+        self.pool_locked.remove(&stake.pool);
+        if !matches!(env::promise_result(0), PromiseResult::Successful(_)) {
+            self.internal_set_escrow_status(id_campaign, EscrowStatus::Unstaked);
+            return false;
+        }
+        self.escrow_stakes.remove(&id_campaign);
+        self.internal_update_pool_unstaked(&stake.pool, 0, stake.unstaked.0);
+        // phan lam tron khi chia so du co the lam unstaked nho hon principal vai yocto
+        let earned = stake.unstaked.0.saturating_sub(stake.principal.0);
+        if earned > 0 {
+            self.internal_distribute_yield(id_campaign, stake.principal.0, earned);
+        }
+        true
+    }
+
+    /// Donor nhan phan loi nhuan theo ti le so tien da donate luc stake
+    pub fn claim_staking_yield(&mut self, id_campaign: IdCampaign) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut donor_yield = self
+            .donor_yields
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign has no staking yield"));
+        let key = (id_campaign, account_id.clone());
+        require!(
+            !self.yield_claimed.contains(&key),
+            "You have claimed the staking yield"
+        );
+        // khong co snapshot: so tien donate chua thay doi ke tu luc stake
+        let donated = self.yield_snapshots.remove(&key).unwrap_or_else(|| {
+            self.contributors
+                .get(&id_campaign)
+                .and_then(|contributor| contributor.get(&account_id))
+                .unwrap_or(0)
+        });
+        require!(
+            donated > 0,
+            "You didn't donate this campaign before it was staked"
+        );
+        let share = (U256::from(donated) * U256::from(donor_yield.total.0)
+            / U256::from(donor_yield.principal.0))
+        .as_u128();
+        // phong truong hop lam tron, khong duoc vuot tong loi nhuan
+        let share = std::cmp::min(share, donor_yield.total.0 - donor_yield.claimed.0);
+        donor_yield.claimed = U128(donor_yield.claimed.0 + share);
+        self.donor_yields.insert(&id_campaign, &donor_yield);
+        self.yield_claimed.insert(&key);
+        if share > 0 {
+            Promise::new(account_id).transfer(share);
+        }
+        U128(share)
+    }
+
+    pub fn get_escrow_stake(&self, id_campaign: IdCampaign) -> Option<EscrowStake> {
+        self.escrow_stakes.get(&id_campaign)
+    }
+
+    pub fn get_donor_yield(&self, id_campaign: IdCampaign) -> Option<DonorYield> {
+        self.donor_yields.get(&id_campaign)
+    }
+}
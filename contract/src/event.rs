@@ -0,0 +1,105 @@
+use near_sdk::{env, json_types::U128, serde::Serialize, serde_json, AccountId};
+
+use crate::IdCampaign;
+
+const EVENT_STANDARD: &str = "campaign";
+const EVENT_VERSION: &str = "1.0.0";
+const NEP171_STANDARD: &str = "nep171";
+const NEP171_VERSION: &str = "1.0.0";
+const NEP141_STANDARD: &str = "nep141";
+const NEP141_VERSION: &str = "1.0.0";
+
+// log theo chuan NEP-297: EVENT_JSON:{"standard", "version", "event", "data"}
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum CampaignEvent {
+    CampaignOwnerTransferred {
+        id_campaign: IdCampaign,
+        old_owner: AccountId,
+        new_owner: AccountId,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMintLog {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Nep171Event {
+    NftMint(Vec<NftMintLog>),
+    NftBurn(Vec<NftMintLog>),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtMintLog {
+    pub owner_id: AccountId,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransferLog {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Nep141Event {
+    #[serde(rename = "ft_mint")]
+    Mint(Vec<FtMintLog>),
+    #[serde(rename = "ft_burn")]
+    Burn(Vec<FtMintLog>),
+    #[serde(rename = "ft_transfer")]
+    Transfer(Vec<FtTransferLog>),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a, T: Serialize> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a T,
+}
+
+fn log_event<T: Serialize>(standard: &'static str, version: &'static str, event: &T) {
+    let log = EventLog {
+        standard,
+        version,
+        event,
+    };
+    env::log_str(&format!(
+        "EVENT_JSON:{}",
+        serde_json::to_string(&log).unwrap()
+    ));
+}
+
+impl CampaignEvent {
+    pub fn emit(&self) {
+        log_event(EVENT_STANDARD, EVENT_VERSION, self);
+    }
+}
+
+impl Nep141Event {
+    pub fn emit(&self) {
+        log_event(NEP141_STANDARD, NEP141_VERSION, self);
+    }
+}
+
+impl Nep171Event {
+    pub fn emit(&self) {
+        log_event(NEP171_STANDARD, NEP171_VERSION, self);
+    }
+}
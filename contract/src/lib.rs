@@ -1,50 +1,259 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::collections::{LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::{U128, U64};
 use near_sdk::{env, near_bindgen, require, AccountId, BorshStorageKey, Promise};
+use std::ops::Bound;
+mod backer_nft;
 mod campaign;
 mod campaign_cancel;
+mod campaign_draft;
+mod campaign_edit;
+mod campaign_metadata;
+mod category;
+mod challenge_pledge;
+mod early_bird;
+mod escrow_staking;
+mod event;
+mod loyalty_points;
+mod manager;
+mod matching_round;
+mod payout;
+mod prepaid_balance;
+mod reward_tier;
+mod sponsor_pool;
+mod subscription;
+mod supporter_badge;
 mod util;
+mod vesting;
+use backer_nft::*;
 use campaign::*;
 use campaign_cancel::*;
+use campaign_edit::*;
+use campaign_metadata::*;
+use category::*;
+use challenge_pledge::*;
+use early_bird::*;
+use escrow_staking::*;
+use event::*;
+use loyalty_points::*;
+use manager::*;
+use matching_round::*;
+use payout::*;
+use reward_tier::*;
+use sponsor_pool::*;
+use subscription::*;
+use supporter_badge::*;
 use util::*;
+use vesting::*;
 
 pub type IdCampaign = u64;
 const ERR_TOTAL_SUPPLY_OVERFLOW: &str = "Total supply overflow";
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+const MAX_REFUND_BATCH: u64 = 20;
+const MAX_DONATE_MANY: usize = 10;
 
 #[derive(BorshSerialize, BorshStorageKey)]
 pub enum StorageKey {
     DetailCampaign,
     Contributors,
-    ContributorsNested,
-    ListCampaign,
+    ContributorsNested { id_campaign: IdCampaign },
+    Slugs,
     ListCampaignSuccess,
     ListCampaignCancel,
+    DeadlineIndex,
+    FailedPayouts,
+    Donors,
+    DonorsNested { id_campaign: IdCampaign },
+    RefundCursor,
+    CampaignsByCreator,
+    CampaignsByCreatorNested { account_hash: Vec<u8> },
+    PendingOwner,
+    Managers,
+    ManagersNested { id_campaign: IdCampaign },
+    CampaignEdits,
+    CampaignEditsNested { id_campaign: IdCampaign },
+    ExtraCategories,
+    CampaignsByCategory,
+    CampaignsByCategoryNested { category_hash: Vec<u8> },
+    CampaignsByTag,
+    CampaignsByTagNested { tag_hash: Vec<u8> },
+    ContributorTiers,
+    BackerNftClaimed,
+    Badges,
+    BadgesPerOwner,
+    BadgesPerOwnerNested { account_hash: Vec<u8> },
+    PointsBalances,
+    PointsMinted,
+    PointsCursor,
+    MatchingRounds,
+    CampaignRounds,
+    RoundContributions,
+    RoundCampaignStats,
+    CampaignMatched,
+    SponsorPools,
+    SponsorMatches,
+    ChallengePledges,
+    WeightedContributors,
+    Subscriptions,
+    SubscriptionPeriods,
+    SubscriptionClaimed,
+    Vestings,
+    Moderators,
+    StakingPools,
+    PoolShares,
+    EscrowStakes,
+    DonorYields,
+    YieldClaimed,
+    Balances,
+    BalanceDonations,
+    PoolUnstaked,
+    YieldSnapshots,
+    PoolLocked,
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Contract {
+    owner_id: AccountId,
     count_campaign: u64, // đếm số lượng campaign đã tạo
     id_index: u64,       // id của mỗi campaign
     campaign: LookupMap<IdCampaign, Campaign>,
     // IDCampaing => AccountId => amount
     contributors: LookupMap<IdCampaign, LookupMap<AccountId, u128>>,
-    list_campaign: Vector<String>, // danh sach cac campaign da khoi tao - danh sach campaign da bi cancel
+    slugs: LookupMap<String, IdCampaign>, // slug (ten campaign da chuan hoa) => IdCampaign
     list_campaign_success: Vector<String>,
     list_campaign_cancel: Vector<CampaignCancel>,
+    // (time_end, IdCampaign) cua cac campaign chua finished, sap xep theo deadline
+    deadline_index: TreeMap<(u64, IdCampaign), ()>,
+    failed_payouts: Vector<FailedPayout>,
+    // IDCampaign => danh sach AccountId theo thu tu donate, dung cho refund_batch
+    donors: LookupMap<IdCampaign, Vector<AccountId>>,
+    refund_cursor: LookupMap<IdCampaign, u64>,
+    // AccountId => cac campaign ma account do la creator
+    campaigns_by_creator: LookupMap<AccountId, UnorderedSet<IdCampaign>>,
+    // IDCampaign => account duoc de xuat lam creator moi
+    pending_owner: LookupMap<IdCampaign, AccountId>,
+    // IDCampaign => manager => quyen duoc creator cap
+    managers: LookupMap<IdCampaign, UnorderedMap<AccountId, ManagerPermissions>>,
+    // IDCampaign => lich su chinh sua metadata/time_end
+    campaign_edits: LookupMap<IdCampaign, Vector<CampaignEdit>>,
+    extra_categories: UnorderedSet<String>,
+    // category/tag => cac campaign da public
+    campaigns_by_category: LookupMap<String, UnorderedSet<IdCampaign>>,
+    campaigns_by_tag: LookupMap<String, UnorderedSet<IdCampaign>>,
+    // (IDCampaign, AccountId) => reward tier ma contributor da chon
+    contributor_tiers: LookupMap<(IdCampaign, AccountId), IdTier>,
+    backer_nft_contract: Option<AccountId>,
+    backer_nft_claimed: LookupSet<(IdCampaign, AccountId)>,
+    // supporter badge (soulbound): token_id => badge
+    badges: UnorderedMap<TokenId, SupporterBadge>,
+    badges_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    badge_min_donation: u128,
+    // loyalty points (NEP-141)
+    points_balances: LookupMap<AccountId, u128>,
+    points_total_supply: u128,
+    // (IDCampaign, AccountId) => so points da mint cho khoan donate do
+    points_minted: LookupMap<(IdCampaign, AccountId), u128>,
+    points_cursor: LookupMap<IdCampaign, u64>,
+    // quadratic funding
+    round_index: u64,
+    matching_rounds: LookupMap<IdRound, MatchingRound>,
+    campaign_rounds: LookupMap<IdCampaign, Vec<IdRound>>,
+    round_contributions: LookupMap<(IdRound, IdCampaign, AccountId), u128>,
+    round_campaign_stats: LookupMap<(IdRound, IdCampaign), RoundCampaignStats>,
+    // IDCampaign => tien match cho campaign, duoc tra cung luc finished_campaign
+    campaign_matched: LookupMap<IdCampaign, u128>,
+    sponsor_pools: LookupMap<IdCampaign, Vec<SponsorPool>>,
+    // (IDCampaign, donor) => so tien da match tu tung pool
+    sponsor_matches: LookupMap<(IdCampaign, AccountId), Vec<u128>>,
+    challenge_pledges: LookupMap<IdCampaign, Vec<ChallengePledge>>,
+    // (IDCampaign, donor) => so tien da nhan bonus early-bird
+    weighted_contributors: LookupMap<(IdCampaign, AccountId), u128>,
+    subscriptions: LookupMap<(IdCampaign, AccountId), Subscription>,
+    // (IDCampaign, chu ky) => tong tien subscriber tra truoc cho chu ky do
+    subscription_periods: LookupMap<(IdCampaign, u64), u128>,
+    // IDCampaign => chu ky tiep theo creator duoc nhan
+    subscription_claimed: LookupMap<IdCampaign, u64>,
+    vestings: LookupMap<IdCampaign, Vesting>,
+    moderators: UnorderedSet<AccountId>,
+    // staking pool duoc owner whitelist
+    staking_pools: UnorderedSet<AccountId>,
+    // pool => tong shares cac campaign dang stake
+    pool_shares: LookupMap<AccountId, u128>,
+    // pool => tong tien da unstake nhung chua withdraw, van nam trong so du tren pool
+    pool_unstaked: LookupMap<AccountId, u128>,
+    // pool dang co thao tac stake/unstake/withdraw chua xong
+    pool_locked: LookupSet<AccountId>,
+    escrow_stakes: LookupMap<IdCampaign, EscrowStake>,
+    yield_destination: YieldDestination,
+    donor_yields: LookupMap<IdCampaign, DonorYield>,
+    yield_claimed: LookupSet<(IdCampaign, AccountId)>,
+    // (IDCampaign, donor) => so tien donate truoc lan thay doi dau tien ke tu luc stake
+    yield_snapshots: LookupMap<(IdCampaign, AccountId), u128>,
+    // prepaid balance cua moi account
+    balances: LookupMap<AccountId, u128>,
+    // (IDCampaign, donor) => so tien donate tu prepaid balance, refund se tra lai balance
+    balance_donations: LookupMap<(IdCampaign, AccountId), u128>,
 }
 
 impl Default for Contract {
     fn default() -> Self {
         Self {
+            owner_id: env::current_account_id(),
             count_campaign: 0,
             id_index: 0,
             campaign: LookupMap::new(StorageKey::DetailCampaign),
             contributors: LookupMap::new(StorageKey::Contributors),
-            list_campaign: Vector::new(StorageKey::ListCampaign),
+            slugs: LookupMap::new(StorageKey::Slugs),
             list_campaign_success: Vector::new(StorageKey::ListCampaignSuccess),
             list_campaign_cancel: Vector::new(StorageKey::ListCampaignCancel),
+            deadline_index: TreeMap::new(StorageKey::DeadlineIndex),
+            failed_payouts: Vector::new(StorageKey::FailedPayouts),
+            donors: LookupMap::new(StorageKey::Donors),
+            refund_cursor: LookupMap::new(StorageKey::RefundCursor),
+            campaigns_by_creator: LookupMap::new(StorageKey::CampaignsByCreator),
+            pending_owner: LookupMap::new(StorageKey::PendingOwner),
+            managers: LookupMap::new(StorageKey::Managers),
+            campaign_edits: LookupMap::new(StorageKey::CampaignEdits),
+            extra_categories: UnorderedSet::new(StorageKey::ExtraCategories),
+            campaigns_by_category: LookupMap::new(StorageKey::CampaignsByCategory),
+            campaigns_by_tag: LookupMap::new(StorageKey::CampaignsByTag),
+            contributor_tiers: LookupMap::new(StorageKey::ContributorTiers),
+            backer_nft_contract: None,
+            backer_nft_claimed: LookupSet::new(StorageKey::BackerNftClaimed),
+            badges: UnorderedMap::new(StorageKey::Badges),
+            badges_per_owner: LookupMap::new(StorageKey::BadgesPerOwner),
+            badge_min_donation: DEFAULT_BADGE_MIN_DONATION,
+            points_balances: LookupMap::new(StorageKey::PointsBalances),
+            points_total_supply: 0,
+            points_minted: LookupMap::new(StorageKey::PointsMinted),
+            points_cursor: LookupMap::new(StorageKey::PointsCursor),
+            round_index: 0,
+            matching_rounds: LookupMap::new(StorageKey::MatchingRounds),
+            campaign_rounds: LookupMap::new(StorageKey::CampaignRounds),
+            round_contributions: LookupMap::new(StorageKey::RoundContributions),
+            round_campaign_stats: LookupMap::new(StorageKey::RoundCampaignStats),
+            campaign_matched: LookupMap::new(StorageKey::CampaignMatched),
+            sponsor_pools: LookupMap::new(StorageKey::SponsorPools),
+            sponsor_matches: LookupMap::new(StorageKey::SponsorMatches),
+            challenge_pledges: LookupMap::new(StorageKey::ChallengePledges),
+            weighted_contributors: LookupMap::new(StorageKey::WeightedContributors),
+            subscriptions: LookupMap::new(StorageKey::Subscriptions),
+            subscription_periods: LookupMap::new(StorageKey::SubscriptionPeriods),
+            subscription_claimed: LookupMap::new(StorageKey::SubscriptionClaimed),
+            vestings: LookupMap::new(StorageKey::Vestings),
+            moderators: UnorderedSet::new(StorageKey::Moderators),
+            staking_pools: UnorderedSet::new(StorageKey::StakingPools),
+            pool_shares: LookupMap::new(StorageKey::PoolShares),
+            pool_unstaked: LookupMap::new(StorageKey::PoolUnstaked),
+            pool_locked: LookupSet::new(StorageKey::PoolLocked),
+            escrow_stakes: LookupMap::new(StorageKey::EscrowStakes),
+            yield_destination: YieldDestination::Campaign,
+            donor_yields: LookupMap::new(StorageKey::DonorYields),
+            yield_claimed: LookupSet::new(StorageKey::YieldClaimed),
+            yield_snapshots: LookupMap::new(StorageKey::YieldSnapshots),
+            balances: LookupMap::new(StorageKey::Balances),
+            balance_donations: LookupMap::new(StorageKey::BalanceDonations),
         }
     }
 }
@@ -58,6 +267,7 @@ impl Contract {
         time_end: U64,
         goal: U128,
         name_campaign: String,
+        options: Option<LaunchOptions>,
     ) -> IdCampaign {
         // phí khởi tạo 1 campaign là 1 near.
         assert_at_least_fee_initial_campaign();
@@ -68,10 +278,45 @@ impl Contract {
             env::panic_str("Time start must lower than Time end");
         }
         let goal = goal.0;
+        let options = options.unwrap_or_default();
+        let recipients = options.recipients.unwrap_or_default();
+        if !recipients.is_empty() {
+            assert_valid_recipients(&recipients);
+        }
+        let metadata = options.metadata.unwrap_or_default();
+        metadata.assert_valid();
+        let category = options.category.map(|c| normalize_slug(&c));
+        if let Some(category) = &category {
+            require!(self.is_category(category), "This category doesn't exist");
+        }
+        let tags = normalize_tags(options.tags.unwrap_or_default());
+        let tiers = build_reward_tiers(options.tiers.unwrap_or_default());
+        let bonus_tiers = options.bonus_tiers.unwrap_or_default();
+        assert_valid_bonus_tiers(&bonus_tiers);
+        let subscription = build_subscription_plan(options.subscription);
+        let vesting_duration = options.vesting_duration_ms.map(|duration| duration.0);
+        assert_valid_vesting_duration(vesting_duration);
+        let slug = normalize_slug(&name_campaign);
+        require!(
+            !slug.is_empty(),
+            "Campaign name must contain letters or digits"
+        );
+        require!(
+            !self.slugs.contains_key(&slug),
+            "A campaign with this name already exists"
+        );
+        let creator = env::signer_account_id();
         let campaign = Campaign {
             id: self.id_index,
             name_campaign,
-            creator: env::signer_account_id(),
+            slug,
+            metadata,
+            category,
+            tags,
+            tiers,
+            beneficiary: options.beneficiary.unwrap_or_else(|| creator.clone()),
+            recipients,
+            creator,
             goal,
             /// chi tieu
             amount: 0, //
@@ -79,15 +324,33 @@ impl Contract {
             time_end,
             finished: false,
             refund: false,
+            draft: options.draft,
+            bonus_tiers,
+            subscription,
+            vesting_duration,
         };
         self.campaign.insert(&campaign.id, &campaign);
-        self.list_campaign.push(&campaign.name_campaign);
-        self.count_campaign += 1;
+        self.slugs.insert(&campaign.slug, &campaign.id);
+        if !campaign.draft {
+            self.internal_list_campaign(&campaign);
+        }
+        self.internal_add_campaign_to_creator(&campaign.creator, campaign.id);
         self.id_index += 1;
-        refund_deposit(init_storage);
+        // creator tra phi storage cho campaign va metadata
+        refund_deposit(env::storage_usage() - init_storage);
         return campaign.id;
     }
 
+    pub fn get_campaign(&self, id_campaign: IdCampaign) -> Option<Campaign> {
+        self.campaign.get(&id_campaign)
+    }
+
+    pub fn get_campaign_by_slug(&self, slug: String) -> Option<Campaign> {
+        self.slugs
+            .get(&normalize_slug(&slug))
+            .and_then(|id_campaign| self.campaign.get(&id_campaign))
+    }
+
     pub fn check_campaign(&self, id_campaign: IdCampaign) -> bool {
         match self.campaign.get(&id_campaign) {
             Some(_x) => true,
@@ -102,64 +365,194 @@ impl Contract {
         if !self.check_campaign(id_campaign) {
             env::panic_str("This campaign doesn't exsit");
         }
-        if self.campaign.get(&id_campaign).unwrap().creator != env::predecessor_account_id() {
+        if !self.has_campaign_permission(
+            &self.campaign.get(&id_campaign).unwrap(),
+            &env::predecessor_account_id(),
+            Permission::Cancel,
+        ) {
             env::panic_str("Just the creator can execute this function");
         }
+        require!(
+            !self.campaign.get(&id_campaign).unwrap().draft,
+            "This campaign is a draft"
+        );
+        self.assert_escrow_not_staked(id_campaign);
         let campaign_cancel = CampaignCancel {
             name_campaign: self.campaign.get(&id_campaign).unwrap().name_campaign,
             time_cancel: env::block_timestamp_ms(),
             canceler: env::predecessor_account_id(),
         };
         self.list_campaign_cancel.push(&campaign_cancel);
+        self.deadline_index.remove(&(
+            self.campaign.get(&id_campaign).unwrap().time_end,
+            id_campaign,
+        ));
         self.count_campaign = self
             .count_campaign
             .checked_sub(1)
             .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
-        self.campaign.remove(&id_campaign);
-        self.list_campaign.swap_remove(id_campaign);
+        let mut campaign = self.campaign.get(&id_campaign).unwrap();
+        if let Some(matched) = self.campaign_matched.remove(&id_campaign) {
+            Promise::new(self.owner_id.clone()).transfer(matched);
+        }
+        campaign.amount -= self.internal_close_sponsor_pools(id_campaign, false);
+        self.internal_refund_challenge_pledges(id_campaign);
+        if campaign.amount > 0 || campaign.subscription.is_some() {
+            // van con tien cua nguoi donate/subscriber, giu lai campaign de ho refund
+            campaign.finished = true;
+            campaign.refund = true;
+            self.campaign.insert(&id_campaign, &campaign);
+        } else {
+            self.campaign.remove(&id_campaign);
+            self.internal_remove_campaign_from_creator(&campaign.creator, id_campaign);
+            self.pending_owner.remove(&id_campaign);
+            self.slugs.remove(&campaign.slug);
+        }
+        self.internal_unindex_category_tags(&campaign);
         refund_deposit(init_storage);
         return true;
     }
 
     #[payable]
-    pub fn donate(&mut self, id_campaign: IdCampaign, amount: U128) {
-        assert_at_least_one_yocto();
-        // let deposit_attached = env::attached_deposit();
-        let deposit_attached = amount.0;
+    pub fn donate(&mut self, id_campaign: IdCampaign, amount: U128, id_tier: Option<IdTier>) {
+        require!(
+            env::attached_deposit() == amount.0,
+            "Attached deposit must equal the donation amount"
+        );
+        self.internal_donate(id_campaign, amount.0, id_tier);
+    }
+
+    /// Donate cho nhieu campaign trong 1 lan goi, deposit phai bang tong so tien.
+    /// Moi phan tu la (campaign, so tien, tier). Chi can 1 campaign khong hop le la
+    /// panic truoc khi donate bat ky campaign nao
+    #[payable]
+    pub fn donate_many(&mut self, donations: Vec<(IdCampaign, U128, Option<IdTier>)>) {
+        require!(
+            !donations.is_empty() && donations.len() <= MAX_DONATE_MANY,
+            "Donations must have between 1 and 10 campaigns"
+        );
+        let mut total: u128 = 0;
+        for (i, (id_campaign, amount, _)) in donations.iter().enumerate() {
+            require!(amount.0 > 0, "Amount must be greater than zero");
+            require!(
+                donations[..i].iter().all(|(id, _, _)| id != id_campaign),
+                "Duplicate campaign in donations"
+            );
+            self.internal_get_donatable_campaign(*id_campaign);
+            total = total
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        }
+        require!(
+            env::attached_deposit() == total,
+            "Attached deposit must equal the sum of donations"
+        );
+        for (id_campaign, amount, id_tier) in donations {
+            self.internal_donate(id_campaign, amount.0, id_tier);
+        }
+    }
+
+    /// Campaign dang nhan donate: ton tai, da publish, chua ket thuc va trong thoi gian donate
+    fn internal_get_donatable_campaign(&self, id_campaign: IdCampaign) -> Campaign {
         if !self.check_campaign(id_campaign) {
             env::panic_str("This campaign doesn't exsit");
         }
-        let time_start = self.campaign.get(&id_campaign).unwrap().time_start;
-        let time_end = self.campaign.get(&id_campaign).unwrap().time_end;
+        let campaign = self.campaign.get(&id_campaign).unwrap();
         require!(
-            env::block_timestamp_ms() >= time_start,
+            env::block_timestamp_ms() >= campaign.time_start,
             "This campaign not start yet"
         );
         require!(
-            env::block_timestamp_ms() <= time_end,
+            env::block_timestamp_ms() <= campaign.time_end,
             "this campaign has end"
         );
+        require!(!campaign.draft, "This campaign is a draft");
+        require!(!campaign.finished, "This campaign was finished");
+        require!(
+            campaign.subscription.is_none(),
+            "Subscription campaigns only accept subscribe"
+        );
+        campaign
+    }
+
+    fn internal_donate(
+        &mut self,
+        id_campaign: IdCampaign,
+        deposit_attached: u128,
+        id_tier: Option<IdTier>,
+    ) {
+        require!(deposit_attached > 0, "Amount must be greater than zero");
         // update amount of campaign
-        let mut old_campaign = self.campaign.get(&id_campaign).unwrap();
+        let mut old_campaign = self.internal_get_donatable_campaign(id_campaign);
         old_campaign.amount = old_campaign
             .amount
             .checked_add(deposit_attached)
             .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
-        self.campaign.insert(&id_campaign, &old_campaign);
 
         //update contributors
-        if let Some(mut result) = self.contributors.get(&id_campaign) {
-            let mut money = result.get(&env::predecessor_account_id()).unwrap();
+        let mut detail = self
+            .contributors
+            .get(&id_campaign)
+            .unwrap_or_else(|| LookupMap::new(StorageKey::ContributorsNested { id_campaign }));
+        self.internal_snapshot_yield_principal(
+            id_campaign,
+            &env::predecessor_account_id(),
+            detail.get(&env::predecessor_account_id()).unwrap_or(0),
+        );
+        if let Some(mut money) = detail.get(&env::predecessor_account_id()) {
             money = money
                 .checked_add(deposit_attached)
                 .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
-            result.insert(&env::predecessor_account_id(), &money);
+            detail.insert(&env::predecessor_account_id(), &money);
         } else {
-            let mut detail: LookupMap<AccountId, u128> =
-                LookupMap::new(StorageKey::ContributorsNested);
             detail.insert(&env::predecessor_account_id(), &deposit_attached);
-            self.contributors.insert(&id_campaign, &detail);
+            let mut donors = self
+                .donors
+                .get(&id_campaign)
+                .unwrap_or_else(|| Vector::new(StorageKey::DonorsNested { id_campaign }));
+            donors.push(&env::predecessor_account_id());
+            self.donors.insert(&id_campaign, &donors);
+        }
+        let raw_donated = detail.get(&env::predecessor_account_id()).unwrap();
+        self.contributors.insert(&id_campaign, &detail);
+
+        let total_donated = self.internal_add_weighted(
+            id_campaign,
+            &env::predecessor_account_id(),
+            weighted_amount(
+                deposit_attached,
+                bonus_bps_at(&old_campaign, env::block_timestamp_ms()),
+            ),
+        );
+        self.internal_record_round_contribution(
+            id_campaign,
+            &env::predecessor_account_id(),
+            deposit_attached,
+            0,
+        );
+        let sponsor_matched = self.internal_draw_sponsor_match(
+            id_campaign,
+            &env::predecessor_account_id(),
+            deposit_attached,
+        );
+        old_campaign.amount = old_campaign
+            .amount
+            .checked_add(sponsor_matched)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        self.internal_mint_supporter_badge(
+            &old_campaign,
+            &env::predecessor_account_id(),
+            raw_donated,
+        );
+        if let Some(id_tier) = id_tier {
+            self.internal_select_tier(
+                &mut old_campaign,
+                &env::predecessor_account_id(),
+                id_tier,
+                total_donated,
+            );
         }
+        self.campaign.insert(&id_campaign, &old_campaign);
     }
 
     #[payable]
@@ -171,6 +564,7 @@ impl Contract {
         if !self.check_campaign(id_campaign) {
             env::panic_str("This campaign doesn't exsit");
         }
+        self.assert_escrow_not_staked(id_campaign);
         // kiểm tra xem user đã từng donate trước đây chưa, nếu chưa return;
         require!(
             self.check_donated(id_campaign),
@@ -183,15 +577,37 @@ impl Contract {
             .get(&env::predecessor_account_id())
             .unwrap();
         let refund = std::cmp::min(amount, amount_donated);
-        Promise::new(env::predecessor_account_id()).transfer(refund);
+        self.internal_snapshot_yield_principal(
+            id_campaign,
+            &env::predecessor_account_id(),
+            amount_donated,
+        );
+        self.internal_refund_payout(
+            id_campaign,
+            env::predecessor_account_id(),
+            refund,
+            refund,
+            PayoutKind::UnDonate,
+        );
 
         //update campaign
         let mut old_campaign = self.campaign.get(&id_campaign).unwrap();
+        let (weighted_released, weighted_left) = self.internal_release_weighted(
+            id_campaign,
+            &env::predecessor_account_id(),
+            refund,
+            amount_donated,
+        );
+        let sponsor_released = self.internal_release_sponsor_match(
+            id_campaign,
+            &env::predecessor_account_id(),
+            refund,
+            amount_donated,
+        );
         old_campaign.amount = old_campaign
             .amount
-            .checked_sub(refund)
+            .checked_sub(refund + sponsor_released)
             .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
-        self.campaign.insert(&id_campaign, &old_campaign);
 
         // update contributor
         let mut amount_contributor = self
@@ -207,6 +623,28 @@ impl Contract {
             .get(&id_campaign)
             .unwrap()
             .insert(&env::predecessor_account_id(), &amount_contributor);
+        self.internal_burn_supporter_badge(
+            id_campaign,
+            &env::predecessor_account_id(),
+            amount_contributor,
+        );
+        self.internal_burn_points(
+            id_campaign,
+            &env::predecessor_account_id(),
+            weighted_released,
+        );
+        self.internal_record_round_contribution(
+            id_campaign,
+            &env::predecessor_account_id(),
+            0,
+            refund,
+        );
+        self.internal_release_tier(
+            &mut old_campaign,
+            &env::predecessor_account_id(),
+            weighted_left,
+        );
+        self.campaign.insert(&id_campaign, &old_campaign);
         refund_deposit(init_storage);
     }
 
@@ -215,32 +653,67 @@ impl Contract {
         let init_storage = env::storage_usage();
         let mut campaign = self.campaign.get(&id_campaign).unwrap();
         let time_end = campaign.time_end;
-        let creator = campaign.creator.clone();
         let finished = campaign.finished;
         let goal = campaign.goal;
-        let amount = campaign.amount;
+        require!(!campaign.draft, "This campaign is a draft");
+        // subscription campaign tra tien theo chu ky qua claim_subscription_period
+        require!(
+            campaign.subscription.is_none(),
+            "Subscription campaigns can't be finished"
+        );
         require!(
             env::block_timestamp_ms() >= time_end,
             "The time of this campaign is not over yet"
         );
         require!(
-            env::predecessor_account_id() == creator,
+            self.has_campaign_permission(
+                &campaign,
+                &env::predecessor_account_id(),
+                Permission::Finalize
+            ),
             "You are not the creator of this campaign"
         );
         if finished {
             panic!("This campaign was finished");
         }
+        self.assert_escrow_not_staked(id_campaign);
+        self.internal_settle_challenge_pledges(&mut campaign);
+        let amount = campaign.amount;
+        // tien match tu quadratic funding round (neu co)
+        let matched = self.campaign_matched.remove(&id_campaign).unwrap_or(0);
         if amount >= goal {
+            let sponsor_used = self.internal_close_sponsor_pools(id_campaign, true);
             self.list_campaign_success.push(&campaign.name_campaign);
             campaign.amount = 0;
             self.campaign.insert(&id_campaign, &campaign);
-            Promise::new(creator).transfer(amount);
+            if let Some(duration) = campaign.vesting_duration {
+                self.vestings.insert(
+                    &id_campaign,
+                    &Vesting {
+                        total: amount + matched,
+                        claimed: 0,
+                        start: env::block_timestamp_ms(),
+                        duration,
+                        donated: amount - sponsor_used,
+                        refund_pool: 0,
+                    },
+                );
+            } else {
+                self.internal_pay_beneficiaries(&campaign, amount + matched);
+            }
+            self.internal_mint_points_batch(id_campaign, MAX_POINTS_BATCH);
         } else {
             campaign.refund = true;
+            // tien sponsor da match tra lai sponsor, khong thuoc ve donor
+            campaign.amount -= self.internal_close_sponsor_pools(id_campaign, false);
             self.campaign.insert(&id_campaign, &campaign);
+            if matched > 0 {
+                Promise::new(self.owner_id.clone()).transfer(matched);
+            }
         }
         campaign.finished = true;
         self.campaign.insert(&id_campaign, &campaign);
+        self.deadline_index.remove(&(time_end, id_campaign));
         refund_deposit(init_storage);
     }
 
@@ -249,13 +722,37 @@ impl Contract {
         let init_storage = env::storage_usage();
         let mut campaign = self.campaign.get(&id_campaign).unwrap();
         require!(campaign.refund, "This campaign can't not refund");
+        require!(
+            campaign.subscription.is_none(),
+            "Subscribers are refunded with cancel_subscription"
+        );
         let mut contributor = self.contributors.get(&id_campaign).unwrap();
 
         // Trường hợp user đã donate và rút lại
-        if let Some(res) = contributor.get(&env::predecessor_account_id()) {
-            Promise::new(env::predecessor_account_id()).transfer(res);
+        if let Some(donated) = contributor.get(&env::predecessor_account_id()) {
+            self.internal_snapshot_yield_principal(
+                id_campaign,
+                &env::predecessor_account_id(),
+                donated,
+            );
+            let res = self.internal_refund_share(id_campaign, donated);
+            self.internal_refund_payout(
+                id_campaign,
+                env::predecessor_account_id(),
+                res,
+                donated,
+                PayoutKind::Refund,
+            );
             //remove out of contributors
             contributor.remove(&env::predecessor_account_id());
+            let weighted =
+                self.internal_remove_weighted(id_campaign, &env::predecessor_account_id(), donated);
+            self.internal_burn_points(
+                id_campaign,
+                &env::predecessor_account_id(),
+                self.internal_refund_share(id_campaign, weighted),
+            );
+            self.internal_burn_supporter_badge(id_campaign, &env::predecessor_account_id(), 0);
             campaign.amount -= res;
             //update amount campaign
             self.campaign.insert(&id_campaign, &campaign);
@@ -265,6 +762,154 @@ impl Contract {
         refund_deposit(init_storage);
     }
 
+    /// Chi doi duoc beneficiary khi campaign chua co ai donate
+    #[payable]
+    pub fn set_campaign_beneficiary(&mut self, id_campaign: IdCampaign, beneficiary: AccountId) {
+        assert_one_yocto();
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        require!(
+            self.donors
+                .get(&id_campaign)
+                .is_none_or(|donors| donors.is_empty()),
+            "Beneficiary can't be changed after the first donation"
+        );
+        campaign.beneficiary = beneficiary;
+        self.campaign.insert(&id_campaign, &campaign);
+    }
+
+    /// Buoc 1: creator de xuat account moi nhan quyen so huu campaign
+    #[payable]
+    pub fn propose_campaign_owner(&mut self, id_campaign: IdCampaign, new_owner: AccountId) {
+        assert_one_yocto();
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        require!(
+            campaign.creator != new_owner,
+            "New owner must be different from the creator"
+        );
+        self.pending_owner.insert(&id_campaign, &new_owner);
+    }
+
+    /// Buoc 2: account duoc de xuat chap nhan quyen so huu
+    #[payable]
+    pub fn accept_campaign_owner(&mut self, id_campaign: IdCampaign) {
+        assert_one_yocto();
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        let new_owner = env::predecessor_account_id();
+        require!(
+            self.pending_owner.get(&id_campaign).as_ref() == Some(&new_owner),
+            "You are not the proposed owner of this campaign"
+        );
+        self.pending_owner.remove(&id_campaign);
+        let old_owner = std::mem::replace(&mut campaign.creator, new_owner.clone());
+        self.campaign.insert(&id_campaign, &campaign);
+        self.internal_remove_campaign_from_creator(&old_owner, id_campaign);
+        self.internal_add_campaign_to_creator(&new_owner, id_campaign);
+        // manager do owner cu cap khong con quyen, owner moi phai cap lai
+        self.internal_clear_campaign_managers(id_campaign);
+        CampaignEvent::CampaignOwnerTransferred {
+            id_campaign,
+            old_owner,
+            new_owner,
+        }
+        .emit();
+    }
+
+    pub fn get_pending_campaign_owner(&self, id_campaign: IdCampaign) -> Option<AccountId> {
+        self.pending_owner.get(&id_campaign)
+    }
+
+    pub fn get_campaigns_by_creator(
+        &self,
+        creator: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<IdCampaign> {
+        match self.campaigns_by_creator.get(&creator) {
+            Some(ids) => ids
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Ai cung co the goi de refund cho toi da `limit` nguoi donate cua campaign
+    /// that bai/bi cancel, lan goi sau tiep tuc tu refund_cursor.
+    pub fn refund_batch(&mut self, id_campaign: IdCampaign, limit: Option<u64>) -> u64 {
+        let mut campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(campaign.refund, "This campaign can't not refund");
+        let donors = match self.donors.get(&id_campaign) {
+            Some(donors) => donors,
+            None => return 0,
+        };
+        let mut contributor = self.contributors.get(&id_campaign).unwrap();
+        let limit = std::cmp::min(limit.unwrap_or(MAX_REFUND_BATCH), MAX_REFUND_BATCH);
+        let mut cursor = self.refund_cursor.get(&id_campaign).unwrap_or(0);
+        let mut refunded = 0;
+        while cursor < donors.len()
+            && refunded < limit
+            && env::used_gas() + GAS_PER_BATCH_REFUND < env::prepaid_gas()
+        {
+            let donor = donors.get(cursor).unwrap();
+            cursor += 1;
+            if let Some(donated) = contributor.remove(&donor) {
+                self.internal_snapshot_yield_principal(id_campaign, &donor, donated);
+                let weighted = self.internal_remove_weighted(id_campaign, &donor, donated);
+                self.internal_burn_points(
+                    id_campaign,
+                    &donor,
+                    self.internal_refund_share(id_campaign, weighted),
+                );
+                self.internal_burn_supporter_badge(id_campaign, &donor, 0);
+                let res = self.internal_refund_share(id_campaign, donated);
+                if res == 0 {
+                    // phan refund bi lam tron ve 0 (campaign bi halt), chi don dep
+                    self.balance_donations.remove(&(id_campaign, donor));
+                    continue;
+                }
+                campaign.amount = campaign
+                    .amount
+                    .checked_sub(res)
+                    .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+                self.internal_refund_payout(id_campaign, donor, res, donated, PayoutKind::Refund);
+                refunded += 1;
+            }
+        }
+        self.campaign.insert(&id_campaign, &campaign);
+        self.refund_cursor.insert(&id_campaign, &cursor);
+        refunded
+    }
+
+    /// Tra ve (cursor, tong so nguoi donate) cua refund_batch
+    pub fn get_refund_progress(&self, id_campaign: IdCampaign) -> (u64, u64) {
+        let total = self
+            .donors
+            .get(&id_campaign)
+            .map_or(0, |donors| donors.len());
+        (self.refund_cursor.get(&id_campaign).unwrap_or(0), total)
+    }
+
     pub fn get_amount_donated(&self, id_campaign: IdCampaign) -> u128 {
         let x = match self
             .contributors
@@ -278,6 +923,80 @@ impl Contract {
         return x.unwrap();
     }
 
+    /// Campaign chua finished co time_end nam trong (now, now + within_ms]
+    pub fn get_campaigns_ending_within(
+        &self,
+        within_ms: U64,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<IdCampaign> {
+        let now = env::block_timestamp_ms();
+        let until = now.saturating_add(within_ms.0);
+        self.deadline_index
+            .range((Bound::Excluded((now, IdCampaign::MAX)), Bound::Unbounded))
+            .take_while(|((time_end, _), _)| *time_end <= until)
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .map(|((_, id), _)| id)
+            .collect()
+    }
+
+    /// Campaign da het han nhung chua goi finished_campaign, dung cho keeper
+    pub fn get_campaigns_pending_finalize(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<IdCampaign> {
+        let now = env::block_timestamp_ms();
+        self.deadline_index
+            .iter()
+            .take_while(|((time_end, _), _)| *time_end <= now)
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .map(|((_, id), _)| id)
+            .collect()
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Just the owner can execute this function"
+        );
+    }
+
+    /// Dua campaign vao cac danh sach public
+    fn internal_list_campaign(&mut self, campaign: &Campaign) {
+        self.deadline_index
+            .insert(&(campaign.time_end, campaign.id), &());
+        self.internal_index_category_tags(campaign);
+        self.count_campaign += 1;
+    }
+
+    fn internal_add_campaign_to_creator(&mut self, creator: &AccountId, id_campaign: IdCampaign) {
+        let mut ids = self.campaigns_by_creator.get(creator).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::CampaignsByCreatorNested {
+                account_hash: env::sha256(creator.as_bytes()),
+            })
+        });
+        ids.insert(&id_campaign);
+        self.campaigns_by_creator.insert(creator, &ids);
+    }
+
+    fn internal_remove_campaign_from_creator(
+        &mut self,
+        creator: &AccountId,
+        id_campaign: IdCampaign,
+    ) {
+        if let Some(mut ids) = self.campaigns_by_creator.get(creator) {
+            ids.remove(&id_campaign);
+            if ids.is_empty() {
+                self.campaigns_by_creator.remove(creator);
+            } else {
+                self.campaigns_by_creator.insert(creator, &ids);
+            }
+        }
+    }
+
     fn check_donated(&self, id_campaign: IdCampaign) -> bool {
         if let Some(res) = self
             .contributors
@@ -298,7 +1017,7 @@ impl Contract {
 mod tests {
     use super::*;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::{testing_env, AccountId, VMContext};
+    use near_sdk::{testing_env, AccountId, PromiseResult, VMContext};
 
     fn get_context(is_view: bool, signer: AccountId) -> VMContext {
         let mut builder = VMContextBuilder::new();
@@ -312,6 +1031,30 @@ mod tests {
         builder.build()
     }
 
+    /// Goi donate voi deposit dung bang so tien donate, sau do tra lai deposit cu
+    fn donate_attached(
+        contract: &mut Contract,
+        id_campaign: IdCampaign,
+        amount: U128,
+        id_tier: Option<IdTier>,
+    ) {
+        let attached = env::attached_deposit();
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(env::current_account_id())
+            .signer_account_id(env::signer_account_id())
+            .predecessor_account_id(env::predecessor_account_id())
+            .block_timestamp(env::block_timestamp())
+            .storage_usage(env::storage_usage())
+            .attached_deposit(amount.0);
+        testing_env!(builder.build());
+        contract.donate(id_campaign, amount, id_tier);
+        builder
+            .storage_usage(env::storage_usage())
+            .attached_deposit(attached);
+        testing_env!(builder.build());
+    }
+
     #[test]
     fn init_default_contract_test() {
         let context = get_context(false, accounts(0));
@@ -319,11 +1062,9 @@ mod tests {
         let contract = Contract::default();
         assert_eq!(contract.count_campaign, 0, "Id_index must equa zero");
         assert_eq!(contract.count_campaign, 0, "count_campaign must equa zero");
-        assert_eq!(
-            contract.list_campaign.len(),
-            0,
-            "list_campaign must equa zero"
-        );
+        assert!(contract
+            .get_campaign_by_slug("khoi-nghiep".to_string())
+            .is_none());
     }
 
     fn init_lunch_campaign(signer: AccountId) {
@@ -335,7 +1076,7 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
     }
 
     #[test]
@@ -348,7 +1089,7 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        let result = contract.lunch_campaign(time_start, time_end, goal, name_campaign);
+        let result = contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
 
         let compare_campaign = contract.campaign.get(&0).unwrap();
         assert_eq!(result, 0);
@@ -401,9 +1142,18 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        assert_eq!(contract.list_campaign.len(), 1);
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        assert_eq!(
+            contract
+                .get_campaign_by_slug("khoi-nghiep".to_string())
+                .unwrap()
+                .id,
+            0
+        );
         assert_eq!(contract.cancel_campaign(0), true);
+        assert!(contract
+            .get_campaign_by_slug("khoi-nghiep".to_string())
+            .is_none());
     }
     #[test]
     #[should_panic(expected = "This campaign doesn't exsit")]
@@ -416,8 +1166,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(2, U128::from(10));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 2, U128::from(10), None);
     }
 
     #[test]
@@ -432,8 +1182,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10))
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None)
     }
 
     #[test]
@@ -448,8 +1198,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10))
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None)
     }
     #[test]
     #[should_panic(expected = "Total supply overflow")]
@@ -462,9 +1212,14 @@ mod tests {
         let time_end = U64::from(1000);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10));
-        contract.donate(0, U128::from(340282366920938463463374607431768211455));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None);
+        donate_attached(
+            &mut contract,
+            0,
+            U128::from(340282366920938463463374607431768211455),
+            None,
+        );
     }
 
     #[test]
@@ -477,8 +1232,8 @@ mod tests {
         let time_end = U64::from(1000);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None);
         assert_eq!(contract.campaign.get(&0).unwrap().amount, 10);
         assert_eq!(
             contract
@@ -501,8 +1256,8 @@ mod tests {
         let time_end = U64::from(1000);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None);
         context.predecessor_account_id = accounts(1);
         testing_env!(context);
         contract.un_donate(0, U128(5));
@@ -518,8 +1273,8 @@ mod tests {
         let time_end = U64::from(1000);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128::from(10));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128::from(10), None);
         contract.un_donate(0, U128::from(10));
         assert_eq!(contract.campaign.get(&0).unwrap().amount, 0);
         assert_eq!(
@@ -545,7 +1300,7 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
         contract.finished_campaign(0);
     }
 
@@ -561,7 +1316,7 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
         context.predecessor_account_id = accounts(2);
         context.attached_deposit = 1 * 10u128.pow(12);
         testing_env!(context);
@@ -580,7 +1335,7 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100 * 10u128.pow(24)); //100near
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context);
         contract.finished_campaign(0);
@@ -601,8 +1356,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100);
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128(200));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128(200), None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context);
         contract.finished_campaign(0);
@@ -622,8 +1377,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100);
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128(90));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128(90), None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context);
         contract.finished_campaign(0);
@@ -644,8 +1399,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100);
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128(200));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128(200), None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context);
         contract.finished_campaign(0);
@@ -662,8 +1417,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100);
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128(50));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128(50), None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context.clone());
         contract.finished_campaign(0);
@@ -682,8 +1437,8 @@ mod tests {
         let time_end = U64::from(100);
         let goal = U128::from(100);
         let name_campaign = String::from("Khoi Nghiep");
-        contract.lunch_campaign(time_start, time_end, goal, name_campaign);
-        contract.donate(0, U128(50));
+        contract.lunch_campaign(time_start, time_end, goal, name_campaign, None);
+        donate_attached(&mut contract, 0, U128(50), None);
         context.block_timestamp = 1_000_000_000;
         testing_env!(context);
         contract.finished_campaign(0);
@@ -698,4 +1453,2288 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn test_deadline_index_views() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let goal = U128::from(100);
+        contract.lunch_campaign(U64(0), U64(500), goal, String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), goal, String::from("B"), None);
+        contract.lunch_campaign(U64(0), U64(100), goal, String::from("C"), None);
+        assert_eq!(
+            contract.get_campaigns_ending_within(U64(1000), None, None),
+            vec![2, 0]
+        );
+        assert!(contract
+            .get_campaigns_pending_finalize(None, None)
+            .is_empty());
+
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context);
+        assert_eq!(
+            contract.get_campaigns_pending_finalize(None, None),
+            vec![2, 0]
+        );
+        contract.finished_campaign(2);
+        assert_eq!(contract.get_campaigns_pending_finalize(None, None), vec![0]);
+        assert_eq!(
+            contract.get_campaigns_ending_within(U64(1000), None, None),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_resolve_payout_failed_restores_balance() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let goal = U128::from(100);
+        contract.lunch_campaign(U64(0), U64(100), goal, String::from("Khoi Nghiep"), None);
+        donate_attached(&mut contract, 0, U128(50), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        contract.refund(0);
+        assert_eq!(contract.campaign.get(&0).unwrap().amount, 0);
+
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_payout(0, accounts(0), U128(50), PayoutKind::Refund));
+        assert_eq!(contract.campaign.get(&0).unwrap().amount, 50);
+        assert_eq!(
+            contract.contributors.get(&0).unwrap().get(&accounts(0)),
+            Some(50)
+        );
+        let failed = contract.get_failed_payouts(None, None);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].kind, PayoutKind::Refund);
+        assert_eq!(failed[0].amount, U128(50));
+        assert!(!failed[0].claimable);
+        // refund lan sau vao prepaid balance thay vi transfer lai
+        contract.refund(0);
+        assert_eq!(contract.get_balance(accounts(0)), U128(50));
+    }
+
+    #[test]
+    fn test_failed_finish_payout_can_be_retried() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(50), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(50), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_payout(0, accounts(0), U128(50), PayoutKind::Finish));
+        // campaign da ket thuc, tien nam trong failed payout thay vi campaign.amount
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 0);
+        assert!(contract.get_failed_payouts(None, None)[0].claimable);
+
+        testing_env!(context);
+        contract.retry_failed_payout(0);
+        assert!(!contract.get_failed_payouts(None, None)[0].claimable);
+    }
+
+    #[test]
+    #[should_panic(expected = "Just the receiver, creator or beneficiary can retry this payout")]
+    fn test_retry_failed_payout_not_allowed() {
+        let context = get_context(false, accounts(0));
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        let mut contract = Contract::default();
+        // campaign khong con ton tai: khong panic, tien giu trong failed payout
+        assert!(!contract.resolve_payout(7, accounts(1), U128(10), PayoutKind::Refund));
+        assert!(contract.get_failed_payouts(None, None)[0].claimable);
+        let mut context = context;
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.retry_failed_payout(0);
+    }
+
+    #[test]
+    fn test_resolve_payout_success() {
+        let context = get_context(false, accounts(0));
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        let mut contract = Contract::default();
+        assert!(contract.resolve_payout(0, accounts(1), U128(10), PayoutKind::Finish));
+        assert!(contract.get_failed_payouts(None, None).is_empty());
+    }
+
+    #[test]
+    fn test_refund_batch_resumable() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let goal = U128::from(100);
+        contract.lunch_campaign(U64(0), U64(100), goal, String::from("Khoi Nghiep"), None);
+        donate_attached(&mut contract, 0, U128(30), None);
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(20), None);
+        context.block_timestamp = 1_000_000_000;
+        context.predecessor_account_id = accounts(0);
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        assert_eq!(contract.refund_batch(0, Some(1)), 1);
+        assert_eq!(contract.get_refund_progress(0), (1, 2));
+        assert_eq!(contract.campaign.get(&0).unwrap().amount, 20);
+        assert_eq!(contract.refund_batch(0, None), 1);
+        assert_eq!(contract.refund_batch(0, None), 0);
+        assert_eq!(contract.get_refund_progress(0), (2, 2));
+        assert_eq!(contract.campaign.get(&0).unwrap().amount, 0);
+        assert!(!contract
+            .contributors
+            .get(&0)
+            .unwrap()
+            .contains_key(&accounts(1)));
+    }
+
+    #[test]
+    fn test_cancel_campaign_with_donation_keeps_refund() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let goal = U128::from(100);
+        contract.lunch_campaign(U64(0), U64(100), goal, String::from("Khoi Nghiep"), None);
+        donate_attached(&mut contract, 0, U128(30), None);
+        assert!(contract.cancel_campaign(0));
+        assert!(contract.campaign.get(&0).unwrap().refund);
+        assert_eq!(contract.refund_batch(0, None), 1);
+        assert_eq!(contract.campaign.get(&0).unwrap().amount, 0);
+    }
+
+    #[test]
+    fn test_set_campaign_beneficiary() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            beneficiary: Some(accounts(2)),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        assert_eq!(contract.campaign.get(&0).unwrap().beneficiary, accounts(2));
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.set_campaign_beneficiary(0, accounts(3));
+        assert_eq!(contract.campaign.get(&0).unwrap().beneficiary, accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Beneficiary can't be changed after the first donation")]
+    fn test_set_campaign_beneficiary_after_donation() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(10), None);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.set_campaign_beneficiary(0, accounts(3));
+    }
+
+    #[test]
+    fn test_split_payout_dust_to_first() {
+        let recipients = vec![
+            PayoutRecipient {
+                account_id: accounts(1),
+                share_bps: 3_333,
+            },
+            PayoutRecipient {
+                account_id: accounts(2),
+                share_bps: 6_667,
+            },
+        ];
+        assert_valid_recipients(&recipients);
+        let payouts = split_payout(100, &recipients);
+        assert_eq!(payouts[0], (accounts(1), 34));
+        assert_eq!(payouts[1], (accounts(2), 66));
+        let payouts = split_payout(u128::MAX, &recipients);
+        assert_eq!(payouts[0].1 + payouts[1].1, u128::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Shares of recipients must sum to 10000 basis points")]
+    fn test_lunch_campaign_invalid_recipients() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            recipients: Some(vec![PayoutRecipient {
+                account_id: accounts(1),
+                share_bps: 5_000,
+            }]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+    }
+
+    #[test]
+    fn test_transfer_campaign_owner() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        assert_eq!(
+            contract.get_campaigns_by_creator(accounts(0), None, None),
+            vec![0]
+        );
+        contract.set_campaign_manager(
+            0,
+            accounts(2),
+            ManagerPermissions {
+                cancel: true,
+                ..Default::default()
+            },
+        );
+
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.propose_campaign_owner(0, accounts(1));
+        assert_eq!(contract.get_pending_campaign_owner(0), Some(accounts(1)));
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.accept_campaign_owner(0);
+
+        assert_eq!(contract.campaign.get(&0).unwrap().creator, accounts(1));
+        assert_eq!(contract.get_pending_campaign_owner(0), None);
+        assert!(contract.get_campaign_managers(0).is_empty());
+        assert!(contract
+            .get_campaigns_by_creator(accounts(0), None, None)
+            .is_empty());
+        assert_eq!(
+            contract.get_campaigns_by_creator(accounts(1), None, None),
+            vec![0]
+        );
+        assert!(near_sdk::test_utils::get_logs()[0].contains("campaign_owner_transferred"));
+    }
+
+    #[test]
+    #[should_panic(expected = "You are not the proposed owner of this campaign")]
+    fn test_accept_campaign_owner_not_proposed() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.propose_campaign_owner(0, accounts(1));
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.accept_campaign_owner(0);
+    }
+
+    #[test]
+    fn test_manager_can_finalize() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        let permissions = ManagerPermissions {
+            finalize: true,
+            ..Default::default()
+        };
+        contract.set_campaign_manager(0, accounts(1), permissions);
+        assert_eq!(contract.get_campaign_managers(0).len(), 1);
+
+        context.predecessor_account_id = accounts(1);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        assert!(contract.campaign.get(&0).unwrap().finished);
+    }
+
+    #[test]
+    #[should_panic(expected = "You are not the creator of this campaign")]
+    fn test_manager_without_permission_cannot_finalize() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        let permissions = ManagerPermissions {
+            cancel: true,
+            ..Default::default()
+        };
+        contract.set_campaign_manager(0, accounts(1), permissions);
+
+        context.predecessor_account_id = accounts(1);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+    }
+
+    #[test]
+    fn test_lunch_campaign_with_metadata() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let metadata = CampaignMetadata {
+            description: String::from("Gay quy khoi nghiep"),
+            reference: Some(String::from("ipfs://bafy")),
+            reference_hash: Some(vec![0u8; 32].into()),
+            ..Default::default()
+        };
+        let options = LaunchOptions {
+            metadata: Some(metadata),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        let campaign = contract.campaign.get(&0).unwrap();
+        assert_eq!(campaign.metadata.description, "Gay quy khoi nghiep");
+        assert_eq!(campaign.metadata.reference.unwrap(), "ipfs://bafy");
+    }
+
+    #[test]
+    #[should_panic(expected = "Description is too long")]
+    fn test_lunch_campaign_metadata_too_long() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            metadata: Some(CampaignMetadata {
+                description: "a".repeat(2001),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+    }
+
+    #[test]
+    fn test_update_metadata_and_extend_deadline() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        let metadata = CampaignMetadata {
+            description: String::from("Mo ta moi"),
+            ..Default::default()
+        };
+        contract.update_campaign_metadata(0, metadata);
+        contract.extend_campaign_deadline(0, U64(500));
+
+        let campaign = contract.campaign.get(&0).unwrap();
+        assert_eq!(campaign.metadata.description, "Mo ta moi");
+        assert_eq!(campaign.time_end, 500);
+        assert_eq!(
+            contract.get_campaigns_ending_within(U64(1000), None, None),
+            vec![0]
+        );
+        let edits = contract.get_campaign_edits(0, None, None);
+        assert_eq!(edits.len(), 2);
+        assert!(matches!(
+            edits[1].change,
+            CampaignChange::Deadline {
+                previous: 100,
+                new: 500
+            }
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "New time end must be later than the current one")]
+    fn test_extend_deadline_cannot_shorten() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        contract.extend_campaign_deadline(0, U64(50));
+    }
+
+    #[test]
+    fn test_draft_campaign_publish() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            draft: true,
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        assert!(contract.get_campaign(0).unwrap().draft);
+        assert_eq!(contract.count_campaign, 0);
+        assert!(contract
+            .get_campaigns_ending_within(U64(1000), None, None)
+            .is_empty());
+
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.update_draft_dates(0, U64(0), U64(300));
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.update_draft_tiers(
+            0,
+            vec![RewardTierArgs {
+                name: String::from("Supporter"),
+                min_pledge: U128(10),
+                description: String::from("Thank you"),
+                max_backers: None,
+            }],
+        );
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.publish_campaign(0);
+        let campaign = contract.get_campaign(0).unwrap();
+        assert!(!campaign.draft);
+        assert_eq!(campaign.time_end, 300);
+        assert_eq!(campaign.tiers[0].name, "Supporter");
+        assert_eq!(contract.count_campaign, 1);
+    }
+
+    #[test]
+    fn test_delete_draft_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            draft: true,
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        let permissions = ManagerPermissions {
+            finalize: true,
+            ..Default::default()
+        };
+        contract.set_campaign_manager(0, accounts(1), permissions);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.delete_draft_campaign(0);
+        assert!(contract.get_campaign(0).is_none());
+        assert!(contract
+            .get_campaigns_by_creator(accounts(0), None, None)
+            .is_empty());
+        // nested map cua managers cung bi xoa
+        let managers: UnorderedMap<AccountId, ManagerPermissions> =
+            UnorderedMap::new(StorageKey::ManagersNested { id_campaign: 0 });
+        assert!(managers.get(&accounts(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign is a draft")]
+    fn test_donate_draft_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            draft: true,
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        donate_attached(&mut contract, 0, U128(10), None);
+    }
+
+    #[test]
+    fn test_get_campaign_by_slug() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from(" Khởi Nghiệp 2023! "),
+            None,
+        );
+        assert_eq!(normalize_slug(" Khởi Nghiệp 2023! "), "khởi-nghiệp-2023");
+        assert_eq!(
+            contract
+                .get_campaign_by_slug(String::from("KHỞI nghiệp 2023"))
+                .unwrap()
+                .id,
+            1
+        );
+        assert!(contract.get_campaign_by_slug(String::from("b")).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "A campaign with this name already exists")]
+    fn test_lunch_campaign_duplicate_slug() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("Khoi Nghiep"),
+            None,
+        );
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("khoi-nghiep"),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_campaigns_by_category_and_tag() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.add_category(String::from("Open Source"));
+        assert!(contract
+            .get_categories()
+            .contains(&String::from("open-source")));
+        let options = LaunchOptions {
+            category: Some(String::from("open-source")),
+            tags: Some(vec![
+                String::from("Rust"),
+                String::from("rust"),
+                String::from("NEAR"),
+            ]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        let options = LaunchOptions {
+            category: Some(String::from("art")),
+            tags: Some(vec![String::from("near")]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("B"),
+            Some(options),
+        );
+
+        assert_eq!(contract.get_campaign(0).unwrap().tags, vec!["rust", "near"]);
+        let in_category =
+            contract.get_campaigns_by_category(String::from(" Open-Source "), None, None);
+        assert_eq!(in_category.len(), 1);
+        assert_eq!(in_category[0].id, 0);
+        assert_eq!(
+            contract
+                .get_campaigns_by_tag(String::from("NEAR"), None, None)
+                .len(),
+            2
+        );
+        assert_eq!(
+            contract
+                .get_campaigns_by_tag(String::from("near"), Some(1), None)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "This category doesn't exist")]
+    fn test_lunch_campaign_unknown_category() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            category: Some(String::from("unknown")),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+    }
+
+    fn init_campaign_with_tiers() -> (Contract, VMContext) {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let tiers = vec![
+            RewardTierArgs {
+                name: String::from("Early bird"),
+                min_pledge: U128(50),
+                description: String::from("Sticker"),
+                max_backers: Some(1),
+            },
+            RewardTierArgs {
+                name: String::from("Supporter"),
+                min_pledge: U128(10),
+                description: String::from("Thank you"),
+                max_backers: None,
+            },
+        ];
+        let options = LaunchOptions {
+            tiers: Some(tiers),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(100),
+            U128(1000),
+            String::from("A"),
+            Some(options),
+        );
+        (contract, context)
+    }
+
+    #[test]
+    fn test_donate_with_reward_tier() {
+        let (mut contract, _) = init_campaign_with_tiers();
+        donate_attached(&mut contract, 0, U128(60), Some(0));
+        assert_eq!(contract.get_contributor_tier(0, accounts(0)), Some(0));
+        assert_eq!(contract.get_campaign(0).unwrap().tiers[0].backers, 1);
+        assert!(!contract.get_campaign(0).unwrap().tiers[0].is_available());
+
+        contract.un_donate(0, U128(40));
+        assert_eq!(contract.get_contributor_tier(0, accounts(0)), None);
+        assert_eq!(contract.get_campaign(0).unwrap().tiers[0].backers, 0);
+
+        donate_attached(&mut contract, 0, U128(5), Some(1));
+        assert_eq!(contract.get_contributor_tier(0, accounts(0)), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be greater than zero")]
+    fn test_donate_zero_amount() {
+        let (mut contract, _) = init_campaign_with_tiers();
+        donate_attached(&mut contract, 0, U128(0), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "This reward tier is sold out")]
+    fn test_donate_reward_tier_sold_out() {
+        let (mut contract, mut context) = init_campaign_with_tiers();
+        donate_attached(&mut contract, 0, U128(60), Some(0));
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        donate_attached(&mut contract, 0, U128(60), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Donation is lower than the minimum pledge of this tier")]
+    fn test_donate_reward_tier_below_minimum() {
+        let (mut contract, _) = init_campaign_with_tiers();
+        donate_attached(&mut contract, 0, U128(20), Some(0));
+    }
+
+    #[test]
+    fn test_claim_backer_nft() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.set_backer_nft_contract(Some(accounts(4)));
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(200), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+
+        context.attached_deposit = 10u128.pow(22);
+        testing_env!(context.clone());
+        contract.claim_backer_nft(0);
+        assert!(contract.is_backer_nft_claimed(0, accounts(0)));
+
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_backer_nft(0, accounts(0), U128(10u128.pow(22))));
+        assert!(!contract.is_backer_nft_claimed(0, accounts(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign didn't succeed")]
+    fn test_claim_backer_nft_failed_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.set_backer_nft_contract(Some(accounts(4)));
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(50), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        contract.claim_backer_nft(0);
+    }
+
+    #[test]
+    fn test_supporter_badge_minted_on_threshold() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.set_badge_min_donation(U128(100));
+        contract.lunch_campaign(U64(0), U64(100), U128(1000), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(60), None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(0)), U128(0));
+        donate_attached(&mut contract, 0, U128(60), None);
+        donate_attached(&mut contract, 0, U128(60), None);
+        assert_eq!(contract.nft_total_supply(), U128(1));
+        let tokens = contract.nft_tokens_for_owner(accounts(0), None, None);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, format!("0:{}", accounts(0)));
+        assert!(contract.nft_token(tokens[0].token_id.clone()).is_some());
+    }
+
+    #[test]
+    fn test_supporter_badge_uses_raw_amount_and_burned_on_un_donate() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.set_badge_min_donation(U128(100));
+        let options = LaunchOptions {
+            bonus_tiers: Some(vec![BonusTier {
+                until_bps: 5_000,
+                bonus_bps: 5_000,
+            }]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(1000),
+            U128(1000),
+            String::from("A"),
+            Some(options),
+        );
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        // trong so 105 nhung chi donate 70
+        donate_attached(&mut contract, 0, U128(70), None);
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        donate_attached(&mut contract, 0, U128(30), None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(1)), U128(1));
+        contract.un_donate(0, U128(1));
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        assert_eq!(contract.nft_supply_for_owner(accounts(1)), U128(0));
+        assert!(contract.nft_token(format!("0:{}", accounts(1))).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Supporter badges are soulbound and can't be transferred")]
+    fn test_supporter_badge_not_transferable() {
+        let context = get_context(false, accounts(0));
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.nft_transfer(accounts(1), String::from("0:alice"), None, None);
+    }
+
+    #[test]
+    fn test_loyalty_points_minted_on_success() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(70), None);
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(50), None);
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+
+        assert_eq!(contract.ft_balance_of(accounts(0)), U128(70));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(50));
+        assert_eq!(contract.ft_total_supply(), U128(120));
+        assert_eq!(contract.mint_points_batch(0), 0);
+        assert_eq!(contract.ft_metadata().decimals, 24);
+
+        assert!(contract.storage_balance_of(accounts(2)).is_none());
+        testing_env!(context.clone());
+        contract.storage_deposit(Some(accounts(2)), None);
+        assert!(contract.storage_balance_of(accounts(2)).is_some());
+
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.ft_transfer(accounts(2), U128(20), None);
+        assert_eq!(contract.ft_balance_of(accounts(0)), U128(50));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(20));
+        assert!(near_sdk::test_utils::get_logs()[0].contains("\"event\":\"ft_transfer\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "The receiver is not registered, call storage_deposit first")]
+    fn test_loyalty_points_transfer_to_unregistered() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(100), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.ft_transfer(accounts(2), U128(20), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+    fn test_loyalty_points_storage_deposit_too_small() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 1;
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.storage_deposit(Some(accounts(2)), None);
+    }
+
+    #[test]
+    fn test_loyalty_points_not_minted_on_failure() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("A"), None);
+        donate_attached(&mut contract, 0, U128(70), None);
+        context.block_timestamp = 1_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        contract.refund(0);
+        assert_eq!(contract.ft_total_supply(), U128(0));
+    }
+
+    #[test]
+    fn test_compute_quadratic_match() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(99), 9);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        // campaign 0: 4 donor x 100, campaign 1: 1 donor x 400 => chi campaign 0 duoc match
+        let stats = vec![
+            RoundCampaignStats {
+                sqrt_sum: 40,
+                total: 400,
+                matched: 0,
+            },
+            RoundCampaignStats {
+                sqrt_sum: 20,
+                total: 400,
+                matched: 0,
+            },
+        ];
+        assert_eq!(compute_quadratic_match(1000, &stats), vec![1000, 0]);
+    }
+
+    #[test]
+    fn test_matching_round_credited_at_payout() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+        context.attached_deposit = 1000;
+        testing_env!(context.clone());
+        let id_round = contract.create_matching_round(U64(0), U64(1000), vec![0, 1]);
+
+        context.attached_deposit = 2 * 10u128.pow(24);
+        for donor in 1..5 {
+            context.predecessor_account_id = accounts(donor);
+            testing_env!(context.clone());
+            donate_attached(&mut contract, 0, U128(100), None);
+        }
+        donate_attached(&mut contract, 1, U128(400), None);
+        assert_eq!(contract.get_round_campaign_stats(id_round, 0).sqrt_sum, 40);
+
+        context.block_timestamp = 1_500_000_000;
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.finalize_matching_round(id_round),
+            vec![U128(1000), U128(0)]
+        );
+        assert_eq!(contract.get_campaign_matched(0), U128(1000));
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        assert_eq!(contract.get_campaign_matched(0), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign is a draft")]
+    fn test_matching_round_rejects_draft() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            draft: true,
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(2000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        context.attached_deposit = 1000;
+        testing_env!(context);
+        contract.create_matching_round(U64(0), U64(1000), vec![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Subscription campaigns can't join a matching round")]
+    fn test_matching_round_rejects_subscription() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 1000;
+        testing_env!(context);
+        contract.create_matching_round(U64(0), U64(1000), vec![0]);
+    }
+
+    #[test]
+    fn test_matching_round_added_to_vesting_after_success() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            vesting_duration_ms: Some(U64(1000)),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(500),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        context.attached_deposit = 1000;
+        testing_env!(context.clone());
+        let id_round = contract.create_matching_round(U64(0), U64(1000), vec![0]);
+
+        context.attached_deposit = 2 * 10u128.pow(24);
+        for donor in 1..3 {
+            context.predecessor_account_id = accounts(donor);
+            testing_env!(context.clone());
+            donate_attached(&mut contract, 0, U128(100), None);
+        }
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 600_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        assert_eq!(contract.get_vesting(0).unwrap().total, U128(200));
+
+        context.block_timestamp = 1_500_000_000;
+        testing_env!(context);
+        assert_eq!(contract.finalize_matching_round(id_round), vec![U128(1000)]);
+        // tien match tra dan theo vesting, khong tra thang cho beneficiary
+        let vesting = contract.get_vesting(0).unwrap();
+        assert_eq!(vesting.total, U128(1200));
+        assert_eq!(vesting.claimed, U128(0));
+    }
+
+    #[test]
+    fn test_sponsor_pool_match_and_cap() {
+        let near = 10u128.pow(24);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * near;
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(10 * near), String::from("A"), None);
+
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 3 * near / 2;
+        testing_env!(context.clone());
+        contract.add_sponsor_pool(0, 10_000);
+
+        context.attached_deposit = 2 * near;
+        for donor in 1..4 {
+            context.predecessor_account_id = accounts(donor);
+            testing_env!(context.clone());
+            donate_attached(&mut contract, 0, U128(near), None);
+        }
+        // pool chi con 0.5 NEAR cho donor thu 2, donor thu 3 khong duoc match
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 9 * near / 2);
+        assert_eq!(contract.get_sponsor_pools(0)[0].used, U128(3 * near / 2));
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        // campaign that bai: phan match khong con nam trong campaign
+        let campaign = contract.get_campaign(0).unwrap();
+        assert!(campaign.refund);
+        assert_eq!(campaign.amount, 3 * near);
+        assert!(contract.get_sponsor_pools(0).is_empty());
+    }
+
+    #[test]
+    fn test_sponsor_pool_released_on_un_donate() {
+        let near = 10u128.pow(24);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * near;
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(10 * near), String::from("A"), None);
+
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        contract.add_sponsor_pool(0, 20_000);
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(near), None);
+        assert_eq!(contract.get_sponsor_pools(0)[0].used, U128(2 * near));
+        contract.un_donate(0, U128(near / 2));
+        assert_eq!(contract.get_sponsor_pools(0)[0].used, U128(near));
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 3 * near / 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must equal the donation amount")]
+    fn test_donate_unbacked_amount() {
+        let near = 10u128.pow(24);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * near;
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(10 * near), String::from("A"), None);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        contract.add_sponsor_pool(0, 10_000);
+
+        // 1 yocto khong the rut het pool cua sponsor
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.donate(0, U128(2 * near), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign is a draft")]
+    fn test_sponsor_pool_on_draft() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            draft: true,
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(2000),
+            U128(1000),
+            String::from("A"),
+            Some(options),
+        );
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        contract.add_sponsor_pool(0, 10_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Subscription campaigns only accept subscribe")]
+    fn test_sponsor_pool_on_subscription() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        contract.add_sponsor_pool(0, 10_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sponsor pool must be at least 1 NEAR")]
+    fn test_sponsor_pool_below_minimum() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(1000), String::from("A"), None);
+        context.attached_deposit = 150;
+        testing_env!(context);
+        contract.add_sponsor_pool(0, 10_000);
+    }
+
+    #[test]
+    fn test_challenge_pledges_settled_on_finish() {
+        let unit = 10u128.pow(23);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(3 * unit), String::from("A"), None);
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(unit), None);
+        // accounts(2) dat dieu kien nho hon so tien nguoi khac da donate
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = unit;
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(unit));
+        // accounts(3) chi dat dieu kien khi pledge cua accounts(2) duoc tinh
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(2 * unit));
+        // accounts(4) khong bao gio dat dieu kien
+        context.predecessor_account_id = accounts(4);
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(10 * unit));
+        assert_eq!(contract.get_challenge_pledges(0).len(), 3);
+
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        assert!(contract.get_challenge_pledges(0).is_empty());
+        let contributor = contract.contributors.get(&0).unwrap();
+        assert_eq!(contributor.get(&accounts(3)), Some(unit));
+        assert_eq!(contributor.get(&accounts(4)), None);
+        assert!(!contract.get_campaign(0).unwrap().refund);
+    }
+
+    #[test]
+    fn test_challenge_pledges_partly_met() {
+        let unit = 10u128.pow(23);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(10 * unit), String::from("A"), None);
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(2 * unit), None);
+        // accounts(2) dat dieu kien, accounts(3) can 4 unit nhung chi co 3 unit tu nguoi khac
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = unit;
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(2 * unit));
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(4 * unit));
+
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.finished_campaign(0);
+        let contributor = contract.contributors.get(&0).unwrap();
+        assert_eq!(contributor.get(&accounts(2)), Some(unit));
+        assert_eq!(contributor.get(&accounts(3)), None);
+        let campaign = contract.get_campaign(0).unwrap();
+        // khong dat goal: pledge da duoc cong vao se duoc refund nhu donate thuong
+        assert!(campaign.refund);
+        assert_eq!(campaign.amount, 3 * unit);
+    }
+
+    #[test]
+    fn test_challenge_pledge_own_pledges_not_counted() {
+        let unit = 10u128.pow(23);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(unit), String::from("A"), None);
+
+        // pledge thu 2 chi dat neu tinh ca pledge thu nhat cua chinh accounts(2)
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = unit;
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(0));
+        contract.pledge_challenge(0, U128(unit));
+
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        let contributor = contract.contributors.get(&0).unwrap();
+        assert_eq!(contributor.get(&accounts(2)), Some(unit));
+
+        // refund pledge that bai duoc giu lai de retry, khong cong vao campaign
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_payout(0, accounts(2), U128(unit), PayoutKind::Pledge));
+        assert!(contract.get_failed_payouts(None, None)[0].claimable);
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 0);
+        assert_eq!(contributor.get(&accounts(2)), Some(unit));
+    }
+
+    #[test]
+    #[should_panic(expected = "Subscription campaigns only accept subscribe")]
+    fn test_challenge_pledge_on_subscription() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+        context.attached_deposit = 10u128.pow(23);
+        testing_env!(context);
+        contract.pledge_challenge(0, U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pledge must be at least 0.1 NEAR")]
+    fn test_challenge_pledge_below_minimum() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(300), String::from("A"), None);
+        context.attached_deposit = 100;
+        testing_env!(context);
+        contract.pledge_challenge(0, U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "this campaign has end")]
+    fn test_cancel_challenge_pledges_after_end() {
+        let unit = 10u128.pow(23);
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(3 * unit), String::from("A"), None);
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = unit;
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(unit));
+
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        assert_eq!(contract.cancel_challenge_pledges(0), U128(unit));
+        context.attached_deposit = unit;
+        testing_env!(context.clone());
+        contract.pledge_challenge(0, U128(unit));
+
+        context.attached_deposit = 1;
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.cancel_challenge_pledges(0);
+    }
+
+    #[test]
+    fn test_early_bird_weighted_contribution() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            bonus_tiers: Some(vec![
+                BonusTier {
+                    until_bps: 2_500,
+                    bonus_bps: 5_000,
+                },
+                BonusTier {
+                    until_bps: 5_000,
+                    bonus_bps: 2_000,
+                },
+            ]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(1000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+        context.block_timestamp = 300_000_000;
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+        context.block_timestamp = 600_000_000;
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+        // 150 + 120 + 100
+        assert_eq!(
+            contract.get_weighted_contribution(0, accounts(1)),
+            U128(370)
+        );
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 300);
+    }
+
+    #[test]
+    fn test_early_bird_tier_boundary_and_un_donate() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            bonus_tiers: Some(vec![
+                BonusTier {
+                    until_bps: 2_500,
+                    bonus_bps: 5_000,
+                },
+                BonusTier {
+                    until_bps: 5_000,
+                    bonus_bps: 2_000,
+                },
+            ]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(1000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+
+        context.predecessor_account_id = accounts(1);
+        // dung moc until_bps thi thuoc tier sau
+        context.block_timestamp = 250_000_000;
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+        context.block_timestamp = 500_000_000;
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+        assert_eq!(
+            contract.get_weighted_contribution(0, accounts(1)),
+            U128(220)
+        );
+        // rut mot nua thi weighted giam theo cung ti le
+        contract.un_donate(0, U128(100));
+        assert_eq!(
+            contract.get_weighted_contribution(0, accounts(1)),
+            U128(110)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Bonus tiers must be sorted and end within 10000 basis points")]
+    fn test_early_bird_unsorted_tiers() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            bonus_tiers: Some(vec![
+                BonusTier {
+                    until_bps: 5_000,
+                    bonus_bps: 2_000,
+                },
+                BonusTier {
+                    until_bps: 2_500,
+                    bonus_bps: 5_000,
+                },
+            ]),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(1000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+    }
+
+    #[test]
+    fn test_subscription_claim_and_cancel() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            subscription: Some(SubscriptionPlanArgs {
+                period_ms: U64(1000),
+                price: U128(10),
+            }),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(10000),
+            U128(0),
+            String::from("A"),
+            Some(options),
+        );
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 30;
+        testing_env!(context.clone());
+        contract.subscribe(0, 3);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 50;
+        context.block_timestamp = 500_000_000;
+        testing_env!(context.clone());
+        contract.subscribe(0, 5);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 1_500_000_000;
+        testing_env!(context.clone());
+        assert_eq!(contract.claim_subscription_period(0), U128(20));
+        assert_eq!(contract.get_subscription_claimed_periods(0), 1);
+
+        // dang o chu ky 1: nhan lai tien cua chu ky 2, 3, 4
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        assert_eq!(contract.cancel_subscription(0), U128(30));
+        assert_eq!(
+            contract.get_subscription(0, accounts(2)).unwrap().periods,
+            2
+        );
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        assert_eq!(contract.claim_subscription_period(0), U128(20));
+        assert_eq!(contract.claim_subscription_period(0), U128(10));
+    }
+
+    fn subscription_campaign(context: &mut VMContext) -> Contract {
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            subscription: Some(SubscriptionPlanArgs {
+                period_ms: U64(1000),
+                price: U128(10),
+            }),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(10000),
+            U128(0),
+            String::from("A"),
+            Some(options),
+        );
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 50;
+        testing_env!(context.clone());
+        contract.subscribe(0, 5);
+        contract
+    }
+
+    #[test]
+    fn test_subscription_refunded_after_cancel_campaign() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 2_500_000_000;
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        assert_eq!(contract.claim_subscription_period(0), U128(10));
+        assert!(contract.cancel_campaign(0));
+
+        // chu ky 0 da claim, nhan lai chu ky 1..4 ke ca chu ky da qua ma creator chua claim
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        assert_eq!(contract.cancel_subscription(0), U128(40));
+        assert!(contract.get_subscription(0, accounts(1)).is_none());
+    }
+
+    #[test]
+    fn test_resubscribe_keeps_unclaimed_periods_refundable() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            subscription: Some(SubscriptionPlanArgs {
+                period_ms: U64(1000),
+                price: U128(10),
+            }),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(10000),
+            U128(0),
+            String::from("A"),
+            Some(options),
+        );
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 20;
+        testing_env!(context.clone());
+        contract.subscribe(0, 2);
+        // chu ky 0, 1 da het, subscribe lai cho chu ky 2, 3
+        context.block_timestamp = 2_500_000_000;
+        testing_env!(context.clone());
+        contract.subscribe(0, 2);
+        assert_eq!(
+            contract.get_subscription(0, accounts(1)).unwrap().previous,
+            vec![(0, 2)]
+        );
+
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        assert_eq!(contract.claim_subscription_period(0), U128(10));
+        assert!(contract.cancel_campaign(0));
+
+        // creator chi claim chu ky 0: nhan lai chu ky 1 cua lan truoc va 2, 3
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        assert_eq!(contract.cancel_subscription(0), U128(30));
+        assert!(contract.get_subscription(0, accounts(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign was finished")]
+    fn test_subscription_claim_after_cancel_campaign() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 2_500_000_000;
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        contract.cancel_campaign(0);
+        contract.claim_subscription_period(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Subscription campaigns can't be finished")]
+    fn test_subscription_campaign_not_finished() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = subscription_campaign(&mut context);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 20_000_000_000;
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context);
+        contract.finished_campaign(0);
+    }
+
+    #[test]
+    fn test_vesting_withdraw_and_halt() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            vesting_duration_ms: Some(U64(1000)),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(2000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        contract.add_moderator(accounts(3));
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(300), None);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(100), None);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 2_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        context.block_timestamp = 2_500_000_000;
+        testing_env!(context.clone());
+        assert_eq!(contract.withdraw_vested(0), U128(200));
+
+        context.predecessor_account_id = accounts(3);
+        context.block_timestamp = 2_750_000_000;
+        testing_env!(context.clone());
+        contract.halt_campaign(0);
+        let vesting = contract.get_vesting(0).unwrap();
+        assert!(vesting.halted);
+        assert_eq!(vesting.vested, U128(300));
+        assert_eq!(vesting.claimed, U128(200));
+
+        // phan chua vest (100) chia cho donor theo ti le 3:1
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.refund(0);
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 25);
+    }
+
+    #[test]
+    fn test_refund_batch_cleans_up_zero_share_donor() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.set_badge_min_donation(U128(1));
+        let options = LaunchOptions {
+            vesting_duration_ms: Some(U64(1000)),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(2000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(400), None);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(1), None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(1));
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 2_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        context.block_timestamp = 2_750_000_000;
+        testing_env!(context.clone());
+        contract.halt_campaign(0);
+
+        // phan con lai 101 / 401: refund cua accounts(2) lam tron ve 0
+        assert_eq!(contract.refund_batch(0, None), 1);
+        assert!(contract
+            .contributors
+            .get(&0)
+            .unwrap()
+            .get(&accounts(2))
+            .is_none());
+        assert!(contract
+            .weighted_contributors
+            .get(&(0, accounts(2)))
+            .is_none());
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+    }
+
+    fn vesting_campaign(context: &mut VMContext) -> Contract {
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let options = LaunchOptions {
+            vesting_duration_ms: Some(U64(1000)),
+            ..Default::default()
+        };
+        contract.lunch_campaign(
+            U64(0),
+            U64(2000),
+            U128(100),
+            String::from("A"),
+            Some(options),
+        );
+        contract.add_moderator(accounts(3));
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(400), None);
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 2_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        contract
+    }
+
+    #[test]
+    fn test_vesting_withdraw_after_halt() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = vesting_campaign(&mut context);
+        context.block_timestamp = 2_250_000_000;
+        testing_env!(context.clone());
+        assert_eq!(contract.withdraw_vested(0), U128(100));
+
+        context.predecessor_account_id = accounts(3);
+        context.block_timestamp = 2_500_000_000;
+        testing_env!(context.clone());
+        contract.halt_campaign(0);
+
+        // phan da vest truoc khi halt van rut duoc, sau do khong tang them
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 3_500_000_000;
+        testing_env!(context);
+        assert_eq!(contract.withdraw_vested(0), U128(100));
+        let vesting = contract.get_vesting(0).unwrap();
+        assert_eq!(vesting.vested, U128(200));
+        assert_eq!(vesting.claimed, U128(200));
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "This campaign is fully vested")]
+    fn test_vesting_halt_fully_vested() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = vesting_campaign(&mut context);
+        context.predecessor_account_id = accounts(3);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context);
+        contract.halt_campaign(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Just a moderator can halt a campaign")]
+    fn test_vesting_halt_not_moderator() {
+        let mut context = get_context(false, accounts(0));
+        let mut contract = vesting_campaign(&mut context);
+        context.predecessor_account_id = accounts(1);
+        context.block_timestamp = 2_500_000_000;
+        testing_env!(context);
+        contract.halt_campaign(0);
+    }
+
+    fn staking_context(mut context: VMContext, result: PromiseResult) {
+        context.predecessor_account_id = accounts(0);
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    #[test]
+    fn test_escrow_staking_yield_to_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.add_staking_pool(accounts(5));
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(1000), None);
+
+        context.predecessor_account_id = accounts(0);
+        testing_env!(context.clone());
+        contract.stake_escrow(0, accounts(5));
+        staking_context(
+            context.clone(),
+            PromiseResult::Successful(b"\"0\"".to_vec()),
+        );
+        contract.on_escrow_stake_balance(0);
+        assert_eq!(contract.get_escrow_stake(0).unwrap().shares, U128(1000));
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_escrow_staked(0);
+        assert_eq!(
+            contract.get_escrow_stake(0).unwrap().status,
+            EscrowStatus::Staked
+        );
+
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context.clone());
+        contract.unstake_escrow(0);
+        // pool tra ve tong so du cua contract: principal 1000 + loi nhuan 50
+        staking_context(
+            context.clone(),
+            PromiseResult::Successful(b"\"1050\"".to_vec()),
+        );
+        contract.on_escrow_balance(0);
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_escrow_unstaked(0);
+        assert_eq!(contract.get_escrow_stake(0).unwrap().unstaked, U128(1050));
+
+        testing_env!(context.clone());
+        contract.withdraw_escrow(0);
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_escrow_withdrawn(0));
+        assert!(contract.get_escrow_stake(0).is_none());
+        assert_eq!(contract.get_campaign_matched(0), U128(50));
+
+        testing_env!(context);
+        contract.finished_campaign(0);
+        assert_eq!(contract.get_campaign_matched(0), U128(0));
+    }
+
+    fn stake_escrow_ok(
+        contract: &mut Contract,
+        context: &VMContext,
+        id_campaign: IdCampaign,
+        pool_balance: u128,
+    ) {
+        testing_env!(context.clone());
+        contract.stake_escrow(id_campaign, accounts(5));
+        staking_context(
+            context.clone(),
+            PromiseResult::Successful(format!("\"{}\"", pool_balance).into_bytes()),
+        );
+        contract.on_escrow_stake_balance(id_campaign);
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_escrow_staked(id_campaign);
+    }
+
+    fn unstake_escrow_ok(
+        contract: &mut Contract,
+        context: &VMContext,
+        id_campaign: IdCampaign,
+        pool_balance: u128,
+    ) {
+        testing_env!(context.clone());
+        contract.unstake_escrow(id_campaign);
+        staking_context(
+            context.clone(),
+            PromiseResult::Successful(format!("\"{}\"", pool_balance).into_bytes()),
+        );
+        contract.on_escrow_balance(id_campaign);
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_escrow_unstaked(id_campaign);
+    }
+
+    fn withdraw_escrow_ok(contract: &mut Contract, context: &VMContext, id_campaign: IdCampaign) {
+        testing_env!(context.clone());
+        contract.withdraw_escrow(id_campaign);
+        staking_context(context.clone(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_escrow_withdrawn(id_campaign));
+    }
+
+    #[test]
+    fn test_escrow_staking_yield_to_donors_uses_snapshot() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.add_staking_pool(accounts(5));
+        contract.set_yield_destination(YieldDestination::Donors);
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(600), None);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(400), None);
+
+        context.predecessor_account_id = accounts(0);
+        stake_escrow_ok(&mut contract, &context, 0, 0);
+        // donate trong luc stake khong duoc chia loi nhuan
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        donate_attached(&mut contract, 0, U128(1000), None);
+
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 3_000_000_000;
+        unstake_escrow_ok(&mut contract, &context, 0, 1100);
+        withdraw_escrow_ok(&mut contract, &context, 0);
+        let donor_yield = contract.get_donor_yield(0).unwrap();
+        assert_eq!(donor_yield.total, U128(100));
+        assert_eq!(donor_yield.principal, U128(1000));
+
+        // accounts(1) rut bot sau khi co loi nhuan, van chia theo 600
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        contract.un_donate(0, U128(300));
+        assert_eq!(contract.claim_staking_yield(0), U128(60));
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        assert_eq!(contract.claim_staking_yield(0), U128(40));
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.claim_staking_yield(0);
+        }));
+        assert!(result.is_err());
+        assert_eq!(contract.get_donor_yield(0).unwrap().claimed, U128(100));
+    }
+
+    #[test]
+    fn test_escrow_staking_yield_to_treasury() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.add_staking_pool(accounts(5));
+        contract.set_yield_destination(YieldDestination::Treasury);
+        donate_attached(&mut contract, 0, U128(1000), None);
+
+        stake_escrow_ok(&mut contract, &context, 0, 0);
+        context.block_timestamp = 3_000_000_000;
+        unstake_escrow_ok(&mut contract, &context, 0, 1050);
+        withdraw_escrow_ok(&mut contract, &context, 0);
+        // loi nhuan chuyen cho owner, khong cong vao campaign hay donor
+        assert_eq!(contract.get_campaign_matched(0), U128(0));
+        assert!(contract.get_donor_yield(0).is_none());
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 1000);
+    }
+
+    #[test]
+    fn test_escrow_staking_two_campaigns_share_pool() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+        contract.add_staking_pool(accounts(5));
+        donate_attached(&mut contract, 0, U128(1000), None);
+        donate_attached(&mut contract, 1, U128(3000), None);
+        stake_escrow_ok(&mut contract, &context, 0, 0);
+        stake_escrow_ok(&mut contract, &context, 1, 1000);
+
+        context.block_timestamp = 3_000_000_000;
+        // loi nhuan 10%: so du tren pool 4400
+        unstake_escrow_ok(&mut contract, &context, 0, 4400);
+        assert_eq!(contract.get_escrow_stake(0).unwrap().unstaked, U128(1100));
+        // tien cua campaign 0 chua withdraw van nam trong so du
+        unstake_escrow_ok(&mut contract, &context, 1, 4400);
+        assert_eq!(contract.get_escrow_stake(1).unwrap().unstaked, U128(3300));
+
+        withdraw_escrow_ok(&mut contract, &context, 0);
+        withdraw_escrow_ok(&mut contract, &context, 1);
+        assert_eq!(contract.get_campaign_matched(0), U128(100));
+        assert_eq!(contract.get_campaign_matched(1), U128(300));
+        assert!(contract.pool_unstaked.get(&accounts(5)).is_none());
+        assert!(contract.pool_shares.get(&accounts(5)).is_none());
+    }
+
+    #[test]
+    fn test_escrow_staking_late_stake_gets_no_earlier_yield() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+        contract.add_staking_pool(accounts(5));
+        donate_attached(&mut contract, 0, U128(1000), None);
+        donate_attached(&mut contract, 1, U128(1000), None);
+        stake_escrow_ok(&mut contract, &context, 0, 0);
+        // campaign 1 stake khi pool da co loi nhuan 100 cua campaign 0
+        stake_escrow_ok(&mut contract, &context, 1, 1100);
+        assert_eq!(contract.get_escrow_stake(0).unwrap().shares, U128(1000));
+        assert_eq!(contract.get_escrow_stake(1).unwrap().shares, U128(909));
+
+        context.block_timestamp = 3_000_000_000;
+        // so du 2100: khong co them loi nhuan sau khi campaign 1 stake
+        unstake_escrow_ok(&mut contract, &context, 0, 2100);
+        assert_eq!(contract.get_escrow_stake(0).unwrap().unstaked, U128(1100));
+        unstake_escrow_ok(&mut contract, &context, 1, 2100);
+        assert_eq!(contract.get_escrow_stake(1).unwrap().unstaked, U128(1000));
+
+        withdraw_escrow_ok(&mut contract, &context, 0);
+        withdraw_escrow_ok(&mut contract, &context, 1);
+        assert_eq!(contract.get_campaign_matched(0), U128(100));
+        assert_eq!(contract.get_campaign_matched(1), U128(0));
+        assert!(contract.pool_shares.get(&accounts(5)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "This staking pool is busy, try again later")]
+    fn test_escrow_staking_pool_busy() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+        contract.add_staking_pool(accounts(5));
+        donate_attached(&mut contract, 0, U128(1000), None);
+        donate_attached(&mut contract, 1, U128(1000), None);
+        contract.stake_escrow(0, accounts(5));
+        // campaign 0 chua doc xong so du pool
+        contract.stake_escrow(1, accounts(5));
+    }
+
+    #[test]
+    fn test_escrow_staking_failed_balance_read_unlocks_pool() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.add_staking_pool(accounts(5));
+        donate_attached(&mut contract, 0, U128(1000), None);
+        contract.stake_escrow(0, accounts(5));
+        staking_context(context.clone(), PromiseResult::Failed);
+        contract.on_escrow_stake_balance(0);
+        assert!(contract.get_escrow_stake(0).is_none());
+        assert!(!contract.pool_locked.contains(&accounts(5)));
+        stake_escrow_ok(&mut contract, &context, 0, 0);
+        assert_eq!(
+            contract.get_escrow_stake(0).unwrap().status,
+            EscrowStatus::Staked
+        );
+    }
+
+    #[test]
+    fn test_prepaid_balance_refunds_back_to_ledger() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(10_000), String::from("A"), None);
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        // lan nap dau tru phi storage cua ledger entry
+        let balance = contract.deposit().0;
+        assert!(balance < 10u128.pow(24));
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.donate_from_balance(0, U128(300), None);
+        assert_eq!(contract.get_balance(accounts(1)), U128(balance - 300));
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 300);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.un_donate(0, U128(100));
+        assert_eq!(contract.get_balance(accounts(1)), U128(balance - 200));
+
+        context.predecessor_account_id = accounts(0);
+        context.attached_deposit = 2 * 10u128.pow(24);
+        context.block_timestamp = 3_000_000_000;
+        testing_env!(context.clone());
+        contract.finished_campaign(0);
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        contract.refund(0);
+        assert_eq!(contract.get_balance(accounts(1)), U128(balance));
+
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.withdraw(U128(balance));
+        assert_eq!(contract.get_balance(accounts(1)), U128(0));
+    }
+
+    #[test]
+    fn test_prepaid_balance_failed_withdraw() {
+        let mut context = get_context(false, accounts(0));
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let balance = contract.deposit().0;
+        // lan nap sau khong tao entry moi nen khong mat phi storage
+        assert_eq!(contract.deposit(), U128(balance + 10u128.pow(24)));
+
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.withdraw(U128(balance));
+        assert_eq!(contract.get_balance(accounts(1)), U128(10u128.pow(24)));
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_withdraw(accounts(1), U128(balance)));
+        assert_eq!(
+            contract.get_balance(accounts(1)),
+            U128(balance + 10u128.pow(24))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough prepaid balance")]
+    fn test_prepaid_balance_overdraw() {
+        let mut context = get_context(false, accounts(0));
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        let balance = contract.deposit().0;
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.withdraw(U128(balance + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit must be greater than the storage cost")]
+    fn test_prepaid_balance_deposit_below_storage_cost() {
+        let mut context = get_context(false, accounts(0));
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1000;
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.deposit();
+    }
+
+    #[test]
+    fn test_donate_many() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 300;
+        testing_env!(context);
+        contract.donate_many(vec![(0, U128(100), None), (1, U128(200), None)]);
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 100);
+        assert_eq!(contract.get_campaign(1).unwrap().amount, 200);
+    }
+
+    #[test]
+    fn test_donate_many_with_tier() {
+        let (mut contract, mut context) = init_campaign_with_tiers();
+        contract.lunch_campaign(U64(0), U64(100), U128(100), String::from("B"), None);
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 300;
+        testing_env!(context);
+        contract.donate_many(vec![(0, U128(100), Some(1)), (1, U128(200), None)]);
+        assert_eq!(contract.get_contributor_tier(0, accounts(1)), Some(1));
+        assert_eq!(contract.get_contributor_tier(1, accounts(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate campaign in donations")]
+    fn test_donate_many_duplicate_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+
+        context.attached_deposit = 300;
+        testing_env!(context);
+        contract.donate_many(vec![(0, U128(100), None), (0, U128(200), None)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Donations must have between 1 and 10 campaigns")]
+    fn test_donate_many_too_many_campaigns() {
+        let context = get_context(false, accounts(0));
+        testing_env!(context);
+        let mut contract = Contract::default();
+        contract.donate_many((0..11).map(|id| (id, U128(1), None)).collect());
+    }
+
+    #[test]
+    fn test_donate_many_reverts_on_invalid_campaign() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(5000), U64(6000), U128(100), String::from("B"), None);
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 300;
+        testing_env!(context);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.donate_many(vec![(0, U128(100), None), (1, U128(200), None)]);
+        }));
+        assert!(result.is_err());
+        // campaign 1 chua bat dau: campaign 0 khong bi donate
+        assert_eq!(contract.get_campaign(0).unwrap().amount, 0);
+        assert!(contract.contributors.get(&0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must equal the sum of donations")]
+    fn test_donate_many_wrong_deposit() {
+        let mut context = get_context(false, accounts(0));
+        context.attached_deposit = 2 * 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::default();
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("A"), None);
+        contract.lunch_campaign(U64(0), U64(2000), U128(100), String::from("B"), None);
+
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 250;
+        testing_env!(context);
+        contract.donate_many(vec![(0, U128(100), None), (1, U128(200), None)]);
+    }
 }
@@ -0,0 +1,321 @@
+use near_sdk::{
+    ext_contract,
+    json_types::{Base64VecU8, U128},
+    serde::{Deserialize, Serialize},
+    AccountId, Gas, PromiseOrValue, PromiseResult,
+};
+
+use crate::*;
+
+pub(crate) const MAX_POINTS_BATCH: u64 = 50;
+const GAS_FOR_FT_ON_TRANSFER: Gas = Gas(25_000_000_000_000);
+const GAS_FOR_FT_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
+// storage cua 1 entry points_balances: prefix + account id dai nhat (4 + 64) + u128 + 40 byte record
+const POINTS_ACCOUNT_STORAGE: u128 = 1 + 4 + 64 + 16 + 40;
+
+// metadata cua token theo chuan NEP-148
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FungibleTokenMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    pub decimals: u8,
+}
+
+// NEP-145
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+fn points_storage_cost() -> u128 {
+    env::storage_byte_cost() * POINTS_ACCOUNT_STORAGE
+}
+
+#[allow(dead_code)]
+#[ext_contract(ext_ft_receiver)]
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+impl Contract {
+    fn internal_points_deposit(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.points_balances.get(account_id).unwrap_or(0);
+        self.points_balances.insert(
+            account_id,
+            &balance
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW)),
+        );
+    }
+
+    fn internal_points_withdraw(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.points_balances.get(account_id).unwrap_or(0);
+        require!(balance >= amount, "The account doesn't have enough points");
+        self.points_balances.insert(account_id, &(balance - amount));
+    }
+
+    fn internal_points_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) {
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(amount > 0, "The amount should be a positive number");
+        require!(
+            self.points_balances.contains_key(receiver_id),
+            "The receiver is not registered, call storage_deposit first"
+        );
+        self.internal_points_withdraw(sender_id, amount);
+        self.internal_points_deposit(receiver_id, amount);
+        Nep141Event::Transfer(vec![FtTransferLog {
+            old_owner_id: sender_id.clone(),
+            new_owner_id: receiver_id.clone(),
+            amount: U128(amount),
+            memo,
+        }])
+        .emit();
+    }
+
+    /// Mint points cho toi da `limit` donor cua campaign thanh cong, 1 point = 1 yoctoNEAR da donate
+    pub(crate) fn internal_mint_points_batch(
+        &mut self,
+        id_campaign: IdCampaign,
+        limit: u64,
+    ) -> u64 {
+        let donors = match self.donors.get(&id_campaign) {
+            Some(donors) => donors,
+            None => return 0,
+        };
+        let contributor = self.contributors.get(&id_campaign).unwrap();
+        let mut cursor = self.points_cursor.get(&id_campaign).unwrap_or(0);
+        let mut minted = 0;
+        let mut logs = vec![];
+        while cursor < donors.len() && minted < limit {
+            let donor = donors.get(cursor).unwrap();
+            cursor += 1;
+            if contributor.get(&donor).unwrap_or(0) == 0 {
+                continue;
+            }
+            // points tinh theo so tien da nhan bonus early-bird
+            let amount = self.internal_weighted_contribution(id_campaign, &donor);
+            self.internal_points_deposit(&donor, amount);
+            self.points_total_supply = self
+                .points_total_supply
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+            self.points_minted
+                .insert(&(id_campaign, donor.clone()), &amount);
+            logs.push(FtMintLog {
+                owner_id: donor,
+                amount: U128(amount),
+            });
+            minted += 1;
+        }
+        self.points_cursor.insert(&id_campaign, &cursor);
+        if !logs.is_empty() {
+            Nep141Event::Mint(logs).emit();
+        }
+        minted
+    }
+
+    /// Burn points da mint cho khoan donate bi refund
+    pub(crate) fn internal_burn_points(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        refunded: u128,
+    ) {
+        let key = (id_campaign, account_id.clone());
+        let minted = match self.points_minted.get(&key) {
+            Some(minted) => minted,
+            None => return,
+        };
+        let balance = self.points_balances.get(account_id).unwrap_or(0);
+        let amount = std::cmp::min(std::cmp::min(minted, refunded), balance);
+        if amount == 0 {
+            return;
+        }
+        self.points_balances.insert(account_id, &(balance - amount));
+        self.points_total_supply -= amount;
+        if minted == amount {
+            self.points_minted.remove(&key);
+        } else {
+            self.points_minted.insert(&key, &(minted - amount));
+        }
+        Nep141Event::Burn(vec![FtMintLog {
+            owner_id: account_id.clone(),
+            amount: U128(amount),
+        }])
+        .emit();
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Ai cung co the goi de tiep tuc mint points cho campaign thanh cong co nhieu donor
+    pub fn mint_points_batch(&mut self, id_campaign: IdCampaign) -> u64 {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.finished && !campaign.refund,
+            "This campaign didn't succeed"
+        );
+        self.internal_mint_points_batch(id_campaign, MAX_POINTS_BATCH)
+    }
+
+    // NEP-141
+    #[payable]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.internal_points_transfer(&env::predecessor_account_id(), &receiver_id, amount.0, memo);
+    }
+
+    #[payable]
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_points_transfer(&sender_id, &receiver_id, amount.0, memo);
+        ext_ft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_FT_ON_TRANSFER)
+            .ft_on_transfer(sender_id.clone(), amount, msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_FT_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount),
+            )
+            .into()
+    }
+
+    /// Tra lai phan points ma receiver khong dung toi
+    #[private]
+    pub fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                match near_sdk::serde_json::from_slice::<U128>(&value) {
+                    Ok(unused) => std::cmp::min(amount.0, unused.0),
+                    Err(_) => amount.0,
+                }
+            }
+            _ => amount.0,
+        };
+        if unused > 0 {
+            let receiver_balance = self.points_balances.get(&receiver_id).unwrap_or(0);
+            let refund = std::cmp::min(receiver_balance, unused);
+            if refund > 0 {
+                self.internal_points_transfer(
+                    &receiver_id,
+                    &sender_id,
+                    refund,
+                    Some(String::from("refund")),
+                );
+            }
+            return U128(amount.0 - refund);
+        }
+        amount
+    }
+
+    /// NEP-145: dang ky account de nhan points, deposit phai du phi storage, phan du duoc tra lai
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let cost = points_storage_cost();
+        let refund = if self.points_balances.contains_key(&account_id) {
+            amount
+        } else {
+            require!(
+                amount >= cost,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            self.points_balances.insert(&account_id, &0);
+            amount - cost
+        };
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        StorageBalance {
+            total: U128(cost),
+            available: U128(0),
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        if self.points_balances.contains_key(&account_id) {
+            Some(StorageBalance {
+                total: U128(points_storage_cost()),
+                available: U128(0),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(points_storage_cost()),
+            max: Some(U128(points_storage_cost())),
+        }
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        U128(self.points_total_supply)
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.points_balances.get(&account_id).unwrap_or(0))
+    }
+
+    pub fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: String::from("ft-1.0.0"),
+            name: String::from("Campaign Loyalty Points"),
+            symbol: String::from("CLP"),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::UnorderedMap,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(default)]
+pub struct ManagerPermissions {
+    pub edit_metadata: bool,
+    pub finalize: bool,
+    pub cancel: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum Permission {
+    EditMetadata,
+    Finalize,
+    Cancel,
+}
+
+impl ManagerPermissions {
+    pub fn allows(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::EditMetadata => self.edit_metadata,
+            Permission::Finalize => self.finalize,
+            Permission::Cancel => self.cancel,
+        }
+    }
+}
+
+impl Contract {
+    /// Creator luon co toan quyen, manager chi co cac quyen duoc cap
+    pub(crate) fn has_campaign_permission(
+        &self,
+        campaign: &Campaign,
+        account_id: &AccountId,
+        permission: Permission,
+    ) -> bool {
+        if &campaign.creator == account_id {
+            return true;
+        }
+        self.managers
+            .get(&campaign.id)
+            .and_then(|managers| managers.get(account_id))
+            .is_some_and(|permissions| permissions.allows(permission))
+    }
+
+    /// Xoa toan bo manager cua campaign (vd: khi doi owner)
+    pub(crate) fn internal_clear_campaign_managers(&mut self, id_campaign: IdCampaign) {
+        if let Some(mut managers) = self.managers.remove(&id_campaign) {
+            managers.clear();
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn set_campaign_manager(
+        &mut self,
+        id_campaign: IdCampaign,
+        manager: AccountId,
+        permissions: ManagerPermissions,
+    ) {
+        assert_at_least_one_yocto();
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        let init_storage = env::storage_usage();
+        let mut managers = self
+            .managers
+            .get(&id_campaign)
+            .unwrap_or_else(|| UnorderedMap::new(StorageKey::ManagersNested { id_campaign }));
+        managers.insert(&manager, &permissions);
+        self.managers.insert(&id_campaign, &managers);
+        refund_deposit(env::storage_usage().saturating_sub(init_storage));
+    }
+
+    #[payable]
+    pub fn remove_campaign_manager(&mut self, id_campaign: IdCampaign, manager: AccountId) {
+        assert_one_yocto();
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        if let Some(mut managers) = self.managers.get(&id_campaign) {
+            managers.remove(&manager);
+            self.managers.insert(&id_campaign, &managers);
+        }
+    }
+
+    pub fn get_campaign_managers(
+        &self,
+        id_campaign: IdCampaign,
+    ) -> Vec<(AccountId, ManagerPermissions)> {
+        self.managers
+            .get(&id_campaign)
+            .map_or_else(Vec::new, |managers| managers.to_vec())
+    }
+}
@@ -0,0 +1,241 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+mod u256 {
+    // code sinh ra boi macro, khong can clippy
+    #![allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+pub(crate) use u256::U256;
+
+pub type IdRound = u64;
+
+const MAX_ROUND_CAMPAIGNS: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MatchingRound {
+    pub id: IdRound,
+    pub pool: u128,
+    pub time_start: u64,
+    pub time_end: u64,
+    pub eligible_campaigns: Vec<IdCampaign>,
+    pub finalized: bool,
+}
+
+// thong ke donate cua 1 campaign trong 1 round
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoundCampaignStats {
+    pub sqrt_sum: u128, // tong can bac 2 cua contribution tung donor
+    pub total: u128,    // tong contribution
+    pub matched: u128,  // so tien duoc match sau khi finalize
+}
+
+/// Can bac 2 lam tron xuong
+pub(crate) fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = x / 2 + x % 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Chia pool theo cong thuc quadratic funding: (tong sqrt)^2 - tong contribution
+pub(crate) fn compute_quadratic_match(pool: u128, stats: &[RoundCampaignStats]) -> Vec<u128> {
+    let ideals: Vec<U256> = stats
+        .iter()
+        .map(|s| {
+            let square = U256::from(s.sqrt_sum) * U256::from(s.sqrt_sum);
+            square.saturating_sub(U256::from(s.total))
+        })
+        .collect();
+    let total_ideal = ideals.iter().fold(U256::zero(), |acc, i| acc + *i);
+    if total_ideal.is_zero() {
+        return vec![0; stats.len()];
+    }
+    ideals
+        .iter()
+        .map(|ideal| (U256::from(pool) * *ideal / total_ideal).as_u128())
+        .collect()
+}
+
+impl Contract {
+    /// Ghi nhan contribution vao cac round dang mo ma campaign tham gia
+    pub(crate) fn internal_record_round_contribution(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        added: u128,
+        removed: u128,
+    ) {
+        let rounds = match self.campaign_rounds.get(&id_campaign) {
+            Some(rounds) => rounds,
+            None => return,
+        };
+        let now = env::block_timestamp_ms();
+        for id_round in rounds {
+            let round = self.matching_rounds.get(&id_round).unwrap();
+            if round.finalized || now < round.time_start || now > round.time_end {
+                continue;
+            }
+            let key = (id_round, id_campaign, account_id.clone());
+            let old = self.round_contributions.get(&key).unwrap_or(0);
+            let new = old
+                .checked_add(added)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW))
+                .saturating_sub(removed);
+            let mut stats = self
+                .round_campaign_stats
+                .get(&(id_round, id_campaign))
+                .unwrap_or_default();
+            stats.sqrt_sum = stats.sqrt_sum - isqrt(old) + isqrt(new);
+            stats.total = stats.total - old + new;
+            self.round_campaign_stats
+                .insert(&(id_round, id_campaign), &stats);
+            self.round_contributions.insert(&key, &new);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner tao round, deposit dinh kem la pool de match
+    #[payable]
+    pub fn create_matching_round(
+        &mut self,
+        time_start: U64,
+        time_end: U64,
+        eligible_campaigns: Vec<IdCampaign>,
+    ) -> IdRound {
+        self.assert_owner();
+        require!(
+            time_start.0 < time_end.0,
+            "Time start must lower than Time end"
+        );
+        require!(
+            !eligible_campaigns.is_empty() && eligible_campaigns.len() <= MAX_ROUND_CAMPAIGNS,
+            "A round must have between 1 and 20 campaigns"
+        );
+        let pool = env::attached_deposit();
+        require!(pool > 0, "Matching pool must be greater than zero");
+        let id_round = self.round_index;
+        for id_campaign in eligible_campaigns.iter() {
+            let campaign = self
+                .campaign
+                .get(id_campaign)
+                .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+            // campaign subscription khong bao gio finished, tien match se bi ket lai
+            require!(!campaign.draft, "This campaign is a draft");
+            require!(!campaign.finished, "This campaign was finished");
+            require!(
+                campaign.subscription.is_none(),
+                "Subscription campaigns can't join a matching round"
+            );
+            let mut rounds = self.campaign_rounds.get(id_campaign).unwrap_or_default();
+            require!(!rounds.contains(&id_round), "Duplicate campaign in round");
+            rounds.push(id_round);
+            self.campaign_rounds.insert(id_campaign, &rounds);
+        }
+        let round = MatchingRound {
+            id: id_round,
+            pool,
+            time_start: time_start.0,
+            time_end: time_end.0,
+            eligible_campaigns,
+            finalized: false,
+        };
+        self.matching_rounds.insert(&id_round, &round);
+        self.round_index += 1;
+        id_round
+    }
+
+    /// Ai cung co the finalize sau khi round ket thuc
+    pub fn finalize_matching_round(&mut self, id_round: IdRound) -> Vec<U128> {
+        let mut round = self
+            .matching_rounds
+            .get(&id_round)
+            .unwrap_or_else(|| env::panic_str("This matching round doesn't exist"));
+        require!(!round.finalized, "This matching round was finalized");
+        require!(
+            env::block_timestamp_ms() > round.time_end,
+            "The time of this matching round is not over yet"
+        );
+        let mut stats: Vec<RoundCampaignStats> = round
+            .eligible_campaigns
+            .iter()
+            .map(|id| {
+                self.round_campaign_stats
+                    .get(&(id_round, *id))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let matches = compute_quadratic_match(round.pool, &stats);
+        let mut returned = round.pool;
+        for (i, id_campaign) in round.eligible_campaigns.iter().enumerate() {
+            let matched = matches[i];
+            stats[i].matched = matched;
+            self.round_campaign_stats
+                .insert(&(id_round, *id_campaign), &stats[i]);
+            if matched == 0 {
+                continue;
+            }
+            match self.campaign.get(id_campaign) {
+                // campaign chua ket thuc: cong vao khi finished_campaign tra tien
+                Some(campaign) if !campaign.finished => {
+                    let credited = self.campaign_matched.get(id_campaign).unwrap_or(0);
+                    self.campaign_matched
+                        .insert(id_campaign, &(credited + matched));
+                }
+                // campaign da thanh cong: cong vao vesting neu co, khong thi chia cho recipients
+                Some(campaign) if !campaign.refund => match self.vestings.get(id_campaign) {
+                    Some(mut vesting) => {
+                        vesting.total += matched;
+                        self.vestings.insert(id_campaign, &vesting);
+                    }
+                    None => self.internal_pay_beneficiaries(&campaign, matched),
+                },
+                // campaign that bai/bi huy: tien match tra lai owner
+                _ => continue,
+            }
+            returned -= matched;
+        }
+        round.finalized = true;
+        self.matching_rounds.insert(&id_round, &round);
+        if returned > 0 {
+            Promise::new(self.owner_id.clone()).transfer(returned);
+        }
+        matches.into_iter().map(U128).collect()
+    }
+
+    pub fn get_matching_round(&self, id_round: IdRound) -> Option<MatchingRound> {
+        self.matching_rounds.get(&id_round)
+    }
+
+    pub fn get_round_campaign_stats(
+        &self,
+        id_round: IdRound,
+        id_campaign: IdCampaign,
+    ) -> RoundCampaignStats {
+        self.round_campaign_stats
+            .get(&(id_round, id_campaign))
+            .unwrap_or_default()
+    }
+
+    pub fn get_campaign_matched(&self, id_campaign: IdCampaign) -> U128 {
+        U128(self.campaign_matched.get(&id_campaign).unwrap_or(0))
+    }
+}
@@ -0,0 +1,201 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId, Gas, PromiseResult,
+};
+
+use crate::*;
+
+pub(crate) const TOTAL_SHARE_BPS: u16 = 10_000;
+const MAX_PAYOUT_RECIPIENTS: usize = 10;
+const GAS_FOR_RESOLVE_PAYOUT: Gas = Gas(10_000_000_000_000);
+// gas can de tao transfer + callback cho moi nguoi trong refund_batch
+pub(crate) const GAS_PER_BATCH_REFUND: Gas = Gas(15_000_000_000_000);
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayoutKind {
+    UnDonate,
+    Refund,
+    Finish,
+    // tra lai challenge pledge khong dat dieu kien / bi huy
+    Pledge,
+    // tra lai tien cac chu ky subscription bi huy
+    Subscription,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutRecipient {
+    pub account_id: AccountId,
+    // basis point, tong cac recipient phai bang 10_000
+    pub share_bps: u16,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedPayout {
+    pub id_campaign: IdCampaign,
+    pub receiver: AccountId,
+    pub amount: U128,
+    pub kind: PayoutKind,
+    pub time_failed: u64,
+    // tien van nam trong record (chua tra lai campaign/contributor), co the retry
+    pub claimable: bool,
+}
+
+pub(crate) fn assert_valid_recipients(recipients: &[PayoutRecipient]) {
+    require!(!recipients.is_empty(), "Recipients must not be empty");
+    require!(
+        recipients.len() <= MAX_PAYOUT_RECIPIENTS,
+        "Too many payout recipients"
+    );
+    require!(
+        recipients.iter().all(|r| r.share_bps > 0),
+        "Share of each recipient must be greater than zero"
+    );
+    let total: u32 = recipients.iter().map(|r| r.share_bps as u32).sum();
+    require!(
+        total == TOTAL_SHARE_BPS as u32,
+        "Shares of recipients must sum to 10000 basis points"
+    );
+}
+
+/// Chia amount theo share_bps, phan du do lam tron thuoc ve recipient dau tien
+pub(crate) fn split_payout(amount: u128, recipients: &[PayoutRecipient]) -> Vec<(AccountId, u128)> {
+    let total = TOTAL_SHARE_BPS as u128;
+    let mut payouts: Vec<(AccountId, u128)> = recipients
+        .iter()
+        .map(|r| {
+            let bps = r.share_bps as u128;
+            let share = amount / total * bps + amount % total * bps / total;
+            (r.account_id.clone(), share)
+        })
+        .collect();
+    let paid: u128 = payouts.iter().map(|(_, share)| share).sum();
+    payouts[0].1 += amount - paid;
+    payouts
+}
+
+impl Contract {
+    /// Chuyen tien cho receiver, ket qua duoc kiem tra lai o resolve_payout
+    pub(crate) fn internal_payout(
+        &self,
+        id_campaign: IdCampaign,
+        receiver: AccountId,
+        amount: u128,
+        kind: PayoutKind,
+    ) -> Promise {
+        Promise::new(receiver.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_PAYOUT)
+                .resolve_payout(id_campaign, receiver, U128(amount), kind),
+        )
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Neu transfer that bai (vd: account da bi xoa) thi tien quay ve contract.
+    /// UnDonate/Refund: tra lai so du cho contributor. Cac loai khac hoac campaign da bi xoa:
+    /// giu tien trong failed payout de retry_failed_payout.
+    #[private]
+    pub fn resolve_payout(
+        &mut self,
+        id_campaign: IdCampaign,
+        receiver: AccountId,
+        amount: U128,
+        kind: PayoutKind,
+    ) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return true;
+        }
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .filter(|_| matches!(kind, PayoutKind::UnDonate | PayoutKind::Refund));
+        let claimable = campaign.is_none();
+        if let Some(mut campaign) = campaign {
+            campaign.amount = campaign
+                .amount
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+            self.campaign.insert(&id_campaign, &campaign);
+            // refund cua campaign bi halt da duoc chia ti le, quy doi lai so tien donate
+            let restored = match kind {
+                PayoutKind::Refund => self.internal_unscale_refund(id_campaign, amount.0),
+                _ => amount.0,
+            };
+            let mut contributor = self
+                .contributors
+                .get(&id_campaign)
+                .unwrap_or_else(|| LookupMap::new(StorageKey::ContributorsNested { id_campaign }));
+            let donated = contributor
+                .get(&receiver)
+                .unwrap_or(0)
+                .checked_add(restored)
+                .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+            contributor.insert(&receiver, &donated);
+            self.contributors.insert(&id_campaign, &contributor);
+            self.internal_add_weighted(id_campaign, &receiver, restored);
+            // lan refund sau cong vao prepaid balance thay vi transfer lai
+            let key = (id_campaign, receiver.clone());
+            let tracked = self.balance_donations.get(&key).unwrap_or(0);
+            self.balance_donations.insert(&key, &(tracked + restored));
+        }
+        self.failed_payouts.push(&FailedPayout {
+            id_campaign,
+            receiver,
+            amount,
+            kind,
+            time_failed: env::block_timestamp_ms(),
+            claimable,
+        });
+        false
+    }
+
+    /// Chuyen lai payout that bai ma tien chua duoc tra ve campaign/contributor
+    pub fn retry_failed_payout(&mut self, index: u64) -> Promise {
+        let mut failed = self
+            .failed_payouts
+            .get(index)
+            .unwrap_or_else(|| env::panic_str("This failed payout doesn't exist"));
+        require!(failed.claimable, "This failed payout can't be retried");
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == failed.receiver
+                || self
+                    .campaign
+                    .get(&failed.id_campaign)
+                    .is_some_and(|c| c.creator == caller || c.beneficiary == caller),
+            "Just the receiver, creator or beneficiary can retry this payout"
+        );
+        failed.claimable = false;
+        self.failed_payouts.replace(index, &failed);
+        self.internal_payout(
+            failed.id_campaign,
+            failed.receiver,
+            failed.amount.0,
+            failed.kind,
+        )
+    }
+
+    pub fn get_failed_payouts(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<FailedPayout> {
+        self.failed_payouts
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .collect()
+    }
+}
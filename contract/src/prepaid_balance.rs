@@ -0,0 +1,127 @@
+use near_sdk::{json_types::U128, AccountId, Gas, PromiseResult};
+
+use crate::*;
+
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas(10_000_000_000_000);
+
+impl Contract {
+    fn internal_balance_deposit(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self
+            .balances
+            .get(account_id)
+            .unwrap_or(0)
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        self.balances.insert(account_id, &balance);
+    }
+
+    fn internal_balance_withdraw(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.balances.get(account_id).unwrap_or(0);
+        require!(balance >= amount, "Not enough prepaid balance");
+        if balance == amount {
+            self.balances.remove(account_id);
+        } else {
+            self.balances.insert(account_id, &(balance - amount));
+        }
+    }
+
+    /// Tra tien donate ve cho donor: phan donate tu prepaid balance duoc cong lai
+    /// vao balance, phan con lai chuyen NEAR nhu binh thuong.
+    /// `removed` la so tien bi tru khoi contributors, `amount` la so tien thuc tra
+    /// (nho hon removed khi campaign bi halt)
+    pub(crate) fn internal_refund_payout(
+        &mut self,
+        id_campaign: IdCampaign,
+        receiver: AccountId,
+        amount: u128,
+        removed: u128,
+        kind: PayoutKind,
+    ) {
+        let key = (id_campaign, receiver.clone());
+        let tracked = self.balance_donations.get(&key).unwrap_or(0);
+        let mut credited = 0;
+        if tracked > 0 && removed > 0 {
+            let released = std::cmp::min(tracked, removed);
+            credited = (U256::from(released) * U256::from(amount) / U256::from(removed)).as_u128();
+            if released == tracked {
+                self.balance_donations.remove(&key);
+            } else {
+                self.balance_donations.insert(&key, &(tracked - released));
+            }
+            self.internal_balance_deposit(&receiver, credited);
+        }
+        if amount > credited {
+            self.internal_payout(id_campaign, receiver, amount - credited, kind);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Nap NEAR vao prepaid balance de donate nhieu lan khong can attach deposit.
+    /// Lan nap dau tien tru phi storage cua ledger entry
+    #[payable]
+    pub fn deposit(&mut self) -> U128 {
+        let amount = env::attached_deposit();
+        let account_id = env::predecessor_account_id();
+        let init_storage = env::storage_usage();
+        self.internal_balance_deposit(&account_id, amount);
+        let storage_cost =
+            env::storage_byte_cost() * env::storage_usage().saturating_sub(init_storage) as u128;
+        require!(
+            amount > storage_cost,
+            "Deposit must be greater than the storage cost"
+        );
+        self.internal_balance_withdraw(&account_id, storage_cost);
+        U128(self.balances.get(&account_id).unwrap())
+    }
+
+    #[payable]
+    pub fn withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(amount.0 > 0, "Amount must be greater than zero");
+        self.internal_balance_withdraw(&account_id, amount.0);
+        Promise::new(account_id.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+                .resolve_withdraw(account_id, amount),
+        )
+    }
+
+    /// Transfer that bai thi cong lai vao balance
+    #[private]
+    pub fn resolve_withdraw(&mut self, account_id: AccountId, amount: U128) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected exactly one promise result"
+        );
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return true;
+        }
+        self.internal_balance_deposit(&account_id, amount.0);
+        false
+    }
+
+    /// Donate bang prepaid balance, chi can 1 yocto
+    #[payable]
+    pub fn donate_from_balance(
+        &mut self,
+        id_campaign: IdCampaign,
+        amount: U128,
+        id_tier: Option<IdTier>,
+    ) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(amount.0 > 0, "Amount must be greater than zero");
+        self.internal_balance_withdraw(&account_id, amount.0);
+        self.internal_donate(id_campaign, amount.0, id_tier);
+        let key = (id_campaign, account_id);
+        let tracked = self.balance_donations.get(&key).unwrap_or(0);
+        self.balance_donations.insert(&key, &(tracked + amount.0));
+    }
+
+    pub fn get_balance(&self, account_id: AccountId) -> U128 {
+        U128(self.balances.get(&account_id).unwrap_or(0))
+    }
+}
@@ -0,0 +1,127 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+pub type IdTier = u32;
+
+const MAX_TIERS: usize = 10;
+const MAX_TIER_NAME_LEN: usize = 64;
+const MAX_TIER_DESCRIPTION_LEN: usize = 500;
+
+// tham so tao tier khi lunch_campaign
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardTierArgs {
+    pub name: String,
+    pub min_pledge: U128,
+    pub description: String,
+    pub max_backers: Option<u64>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardTier {
+    pub name: String,
+    pub min_pledge: u128,
+    pub description: String,
+    pub max_backers: Option<u64>,
+    pub backers: u64,
+}
+
+impl RewardTier {
+    pub fn is_available(&self) -> bool {
+        self.max_backers.is_none_or(|max| self.backers < max)
+    }
+}
+
+pub(crate) fn build_reward_tiers(tiers: Vec<RewardTierArgs>) -> Vec<RewardTier> {
+    require!(tiers.len() <= MAX_TIERS, "Too many reward tiers");
+    tiers
+        .into_iter()
+        .map(|tier| {
+            require!(
+                !tier.name.is_empty() && tier.name.len() <= MAX_TIER_NAME_LEN,
+                "Tier name must have between 1 and 64 characters"
+            );
+            require!(
+                tier.description.len() <= MAX_TIER_DESCRIPTION_LEN,
+                "Tier description is too long"
+            );
+            require!(
+                tier.max_backers != Some(0),
+                "Max backers of a tier must be greater than zero"
+            );
+            RewardTier {
+                name: tier.name,
+                min_pledge: tier.min_pledge.0,
+                description: tier.description,
+                max_backers: tier.max_backers,
+                backers: 0,
+            }
+        })
+        .collect()
+}
+
+impl Contract {
+    /// Gan contributor vao tier sau khi donate, tong so tien da donate phai >= min_pledge
+    pub(crate) fn internal_select_tier(
+        &mut self,
+        campaign: &mut Campaign,
+        account_id: &AccountId,
+        id_tier: IdTier,
+        total_donated: u128,
+    ) {
+        let key = (campaign.id, account_id.clone());
+        let current = self.contributor_tiers.get(&key);
+        if current == Some(id_tier) {
+            return;
+        }
+        let tier = campaign
+            .tiers
+            .get_mut(id_tier as usize)
+            .unwrap_or_else(|| env::panic_str("This reward tier doesn't exist"));
+        require!(
+            total_donated >= tier.min_pledge,
+            "Donation is lower than the minimum pledge of this tier"
+        );
+        require!(tier.is_available(), "This reward tier is sold out");
+        tier.backers += 1;
+        if let Some(current) = current {
+            campaign.tiers[current as usize].backers -= 1;
+        }
+        self.contributor_tiers.insert(&key, &id_tier);
+    }
+
+    /// Bo tier cua contributor neu so tien con lai khong du min_pledge
+    pub(crate) fn internal_release_tier(
+        &mut self,
+        campaign: &mut Campaign,
+        account_id: &AccountId,
+        total_donated: u128,
+    ) {
+        let key = (campaign.id, account_id.clone());
+        if let Some(id_tier) = self.contributor_tiers.get(&key) {
+            let tier = &mut campaign.tiers[id_tier as usize];
+            if total_donated < tier.min_pledge {
+                tier.backers -= 1;
+                self.contributor_tiers.remove(&key);
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_contributor_tier(
+        &self,
+        id_campaign: IdCampaign,
+        account_id: AccountId,
+    ) -> Option<IdTier> {
+        self.contributor_tiers.get(&(id_campaign, account_id))
+    }
+}
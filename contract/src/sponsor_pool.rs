@@ -0,0 +1,162 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+const MAX_SPONSOR_POOLS: usize = 5;
+// toi da match 10:1
+const MAX_SPONSOR_RATIO_BPS: u32 = 100_000;
+// pool toi thieu 1 NEAR, tranh spam pool nho chiem het MAX_SPONSOR_POOLS
+const MIN_SPONSOR_POOL_CAP: u128 = 10u128.pow(24);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SponsorPool {
+    pub sponsor: AccountId,
+    // 10_000 = match 1:1
+    pub ratio_bps: u32,
+    pub cap: U128,
+    pub used: U128,
+}
+
+impl Contract {
+    /// Rut tien match tu cac sponsor pool cho 1 lan donate, tra ve tong so tien match
+    pub(crate) fn internal_draw_sponsor_match(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> u128 {
+        let mut pools = match self.sponsor_pools.get(&id_campaign) {
+            Some(pools) => pools,
+            None => return 0,
+        };
+        let key = (id_campaign, account_id.clone());
+        let mut matches = self.sponsor_matches.get(&key).unwrap_or_default();
+        matches.resize(pools.len(), 0);
+        let mut total = 0;
+        for (i, pool) in pools.iter_mut().enumerate() {
+            let wanted = (U256::from(amount) * U256::from(pool.ratio_bps)
+                / U256::from(TOTAL_SHARE_BPS))
+            .as_u128();
+            let matched = std::cmp::min(wanted, pool.cap.0 - pool.used.0);
+            pool.used = U128(pool.used.0 + matched);
+            matches[i] += matched;
+            total += matched;
+        }
+        if total > 0 {
+            self.sponsor_pools.insert(&id_campaign, &pools);
+            self.sponsor_matches.insert(&key, &matches);
+        }
+        total
+    }
+
+    /// Khi un_donate, tra lai pool phan match tuong ung voi so tien rut ra
+    pub(crate) fn internal_release_sponsor_match(
+        &mut self,
+        id_campaign: IdCampaign,
+        account_id: &AccountId,
+        refund: u128,
+        donated: u128,
+    ) -> u128 {
+        let key = (id_campaign, account_id.clone());
+        let (mut pools, mut matches) = match (
+            self.sponsor_pools.get(&id_campaign),
+            self.sponsor_matches.get(&key),
+        ) {
+            (Some(pools), Some(matches)) => (pools, matches),
+            _ => return 0,
+        };
+        let mut total = 0;
+        for (i, matched) in matches.iter_mut().enumerate() {
+            let released = if refund >= donated {
+                *matched
+            } else {
+                (U256::from(*matched) * U256::from(refund) / U256::from(donated)).as_u128()
+            };
+            *matched -= released;
+            pools[i].used = U128(pools[i].used.0 - released);
+            total += released;
+        }
+        self.sponsor_pools.insert(&id_campaign, &pools);
+        self.sponsor_matches.insert(&key, &matches);
+        total
+    }
+
+    /// Dong cac pool khi campaign ket thuc/bi huy. Thanh cong: tra phan chua dung cho
+    /// sponsor. That bai: tra lai toan bo. Tra ve tong tien da match vao campaign.amount
+    pub(crate) fn internal_close_sponsor_pools(
+        &mut self,
+        id_campaign: IdCampaign,
+        success: bool,
+    ) -> u128 {
+        let pools = match self.sponsor_pools.remove(&id_campaign) {
+            Some(pools) => pools,
+            None => return 0,
+        };
+        let mut used = 0;
+        for pool in pools {
+            used += pool.used.0;
+            let returned = if success {
+                pool.cap.0 - pool.used.0
+            } else {
+                pool.cap.0
+            };
+            if returned > 0 {
+                Promise::new(pool.sponsor).transfer(returned);
+            }
+        }
+        used
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sponsor match moi lan donate theo ratio_bps, deposit dinh kem la cap cua pool
+    #[payable]
+    pub fn add_sponsor_pool(&mut self, id_campaign: IdCampaign, ratio_bps: u32) {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        require!(!campaign.draft, "This campaign is a draft");
+        require!(!campaign.finished, "This campaign was finished");
+        require!(
+            campaign.subscription.is_none(),
+            "Subscription campaigns only accept subscribe"
+        );
+        require!(
+            env::block_timestamp_ms() <= campaign.time_end,
+            "this campaign has end"
+        );
+        require!(
+            ratio_bps > 0 && ratio_bps <= MAX_SPONSOR_RATIO_BPS,
+            "Ratio must be between 1 and 100000 basis points"
+        );
+        let cap = env::attached_deposit();
+        require!(
+            cap >= MIN_SPONSOR_POOL_CAP,
+            "Sponsor pool must be at least 1 NEAR"
+        );
+        let mut pools = self.sponsor_pools.get(&id_campaign).unwrap_or_default();
+        require!(
+            pools.len() < MAX_SPONSOR_POOLS,
+            "Too many sponsor pools for this campaign"
+        );
+        pools.push(SponsorPool {
+            sponsor: env::predecessor_account_id(),
+            ratio_bps,
+            cap: U128(cap),
+            used: U128(0),
+        });
+        self.sponsor_pools.insert(&id_campaign, &pools);
+    }
+
+    pub fn get_sponsor_pools(&self, id_campaign: IdCampaign) -> Vec<SponsorPool> {
+        self.sponsor_pools.get(&id_campaign).unwrap_or_default()
+    }
+}
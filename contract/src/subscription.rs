@@ -0,0 +1,251 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+const MAX_SUBSCRIPTION_PERIODS: u64 = 36;
+
+// tham so tao subscription campaign khi lunch_campaign
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionPlanArgs {
+    pub period_ms: U64,
+    pub price: U128,
+}
+
+// chu ky tinh tu time_start: period k = [time_start + k * period_ms, time_start + (k + 1) * period_ms)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionPlan {
+    pub period_ms: u64,
+    pub price: u128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Subscription {
+    pub first_period: u64,
+    pub periods: u64,
+    pub price: U128,
+    // (first_period, periods) cua cac lan subscribe truoc ma creator chua claim het
+    pub previous: Vec<(u64, u64)>,
+}
+
+pub(crate) fn build_subscription_plan(
+    args: Option<SubscriptionPlanArgs>,
+) -> Option<SubscriptionPlan> {
+    args.map(|args| {
+        require!(args.period_ms.0 > 0, "Period must be greater than zero");
+        require!(args.price.0 > 0, "Price must be greater than zero");
+        SubscriptionPlan {
+            period_ms: args.period_ms.0,
+            price: args.price.0,
+        }
+    })
+}
+
+/// Chu ky hien tai cua campaign tai thoi diem now
+fn current_period(campaign: &Campaign, plan: &SubscriptionPlan, now: u64) -> u64 {
+    now.saturating_sub(campaign.time_start) / plan.period_ms
+}
+
+impl Contract {
+    fn internal_get_subscription_plan(
+        &self,
+        id_campaign: IdCampaign,
+    ) -> (Campaign, SubscriptionPlan) {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        let plan = campaign
+            .subscription
+            .clone()
+            .unwrap_or_else(|| env::panic_str("This is not a subscription campaign"));
+        (campaign, plan)
+    }
+
+    fn internal_add_period_amount(&mut self, id_campaign: IdCampaign, period: u64, amount: u128) {
+        let total = self
+            .subscription_periods
+            .get(&(id_campaign, period))
+            .unwrap_or(0)
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        self.subscription_periods
+            .insert(&(id_campaign, period), &total);
+    }
+
+    /// Tru tien cua subscriber khoi cac chu ky [from, end), tra ve so tien duoc tra lai
+    fn internal_release_periods(
+        &mut self,
+        id_campaign: IdCampaign,
+        price: u128,
+        from: u64,
+        end: u64,
+    ) -> u128 {
+        for period in from..end {
+            let total = self
+                .subscription_periods
+                .get(&(id_campaign, period))
+                .unwrap();
+            self.subscription_periods
+                .insert(&(id_campaign, period), &(total - price));
+        }
+        price * end.saturating_sub(from) as u128
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Tra truoc `periods` chu ky, bat dau tu chu ky hien tai
+    #[payable]
+    pub fn subscribe(&mut self, id_campaign: IdCampaign, periods: u64) {
+        let (campaign, plan) = self.internal_get_subscription_plan(id_campaign);
+        require!(!campaign.draft, "This campaign is a draft");
+        require!(!campaign.finished, "This campaign was finished");
+        let now = env::block_timestamp_ms();
+        require!(now >= campaign.time_start, "This campaign not start yet");
+        require!(
+            periods > 0 && periods <= MAX_SUBSCRIPTION_PERIODS,
+            "Periods must be between 1 and 36"
+        );
+        let first_period = current_period(&campaign, &plan, now);
+        let end = (first_period + periods) * plan.period_ms;
+        require!(
+            campaign.time_start + end <= campaign.time_end,
+            "Subscription must end before the campaign ends"
+        );
+        let cost = plan
+            .price
+            .checked_mul(periods as u128)
+            .unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        require!(
+            env::attached_deposit() == cost,
+            "Attached deposit must equal price multiplied by periods"
+        );
+        let key = (id_campaign, env::predecessor_account_id());
+        let mut previous = vec![];
+        if let Some(subscription) = self.subscriptions.get(&key) {
+            require!(
+                subscription.first_period + subscription.periods <= first_period,
+                "You already have an active subscription"
+            );
+            // giu lai cac chu ky cu creator chua claim de con refund duoc khi campaign bi huy
+            let claimed = self.subscription_claimed.get(&id_campaign).unwrap_or(0);
+            previous = subscription.previous;
+            previous.push((subscription.first_period, subscription.periods));
+            previous.retain(|(first, periods)| *periods > 0 && first + periods > claimed);
+        }
+        for period in first_period..first_period + periods {
+            self.internal_add_period_amount(id_campaign, period, plan.price);
+        }
+        self.subscriptions.insert(
+            &key,
+            &Subscription {
+                first_period,
+                periods,
+                price: U128(plan.price),
+                previous,
+            },
+        );
+    }
+
+    /// Huy subscription, nhan lai tien cua cac chu ky chua bat dau.
+    /// Campaign da bi cancel: nhan lai tien cua moi chu ky creator chua claim
+    #[payable]
+    pub fn cancel_subscription(&mut self, id_campaign: IdCampaign) -> U128 {
+        assert_one_yocto();
+        let (campaign, plan) = self.internal_get_subscription_plan(id_campaign);
+        let key = (id_campaign, env::predecessor_account_id());
+        let mut subscription = self
+            .subscriptions
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("You have no subscription for this campaign"));
+        let price = subscription.price.0;
+        let refund = if campaign.refund {
+            let claimed = self.subscription_claimed.get(&id_campaign).unwrap_or(0);
+            let mut ranges = std::mem::take(&mut subscription.previous);
+            ranges.push((subscription.first_period, subscription.periods));
+            let mut refund = 0;
+            for (first, periods) in ranges {
+                let from = std::cmp::max(first, claimed);
+                if from < first + periods {
+                    refund +=
+                        self.internal_release_periods(id_campaign, price, from, first + periods);
+                }
+            }
+            require!(refund > 0, "No future period left to cancel");
+            subscription.periods = 0;
+            refund
+        } else {
+            let end = subscription.first_period + subscription.periods;
+            let keep_until = std::cmp::max(
+                subscription.first_period,
+                current_period(&campaign, &plan, env::block_timestamp_ms()) + 1,
+            );
+            require!(keep_until < end, "No future period left to cancel");
+            subscription.periods = keep_until - subscription.first_period;
+            self.internal_release_periods(id_campaign, price, keep_until, end)
+        };
+        if subscription.periods == 0 && subscription.previous.is_empty() {
+            self.subscriptions.remove(&key);
+        } else {
+            self.subscriptions.insert(&key, &subscription);
+        }
+        self.internal_payout(
+            id_campaign,
+            env::predecessor_account_id(),
+            refund,
+            PayoutKind::Subscription,
+        );
+        U128(refund)
+    }
+
+    /// Creator nhan tien cua chu ky tiep theo da ket thuc, moi lan 1 chu ky
+    pub fn claim_subscription_period(&mut self, id_campaign: IdCampaign) -> U128 {
+        let (campaign, plan) = self.internal_get_subscription_plan(id_campaign);
+        require!(
+            campaign.creator == env::predecessor_account_id(),
+            "You are not the creator of this campaign"
+        );
+        require!(!campaign.finished, "This campaign was finished");
+        let period = self.subscription_claimed.get(&id_campaign).unwrap_or(0);
+        require!(
+            current_period(&campaign, &plan, env::block_timestamp_ms()) > period,
+            "This period is not over yet"
+        );
+        let amount = self
+            .subscription_periods
+            .remove(&(id_campaign, period))
+            .unwrap_or(0);
+        self.subscription_claimed
+            .insert(&id_campaign, &(period + 1));
+        if amount > 0 {
+            self.internal_payout(
+                id_campaign,
+                campaign.beneficiary,
+                amount,
+                PayoutKind::Finish,
+            );
+        }
+        U128(amount)
+    }
+
+    pub fn get_subscription(
+        &self,
+        id_campaign: IdCampaign,
+        account_id: AccountId,
+    ) -> Option<Subscription> {
+        self.subscriptions.get(&(id_campaign, account_id))
+    }
+
+    /// So chu ky creator da nhan
+    pub fn get_subscription_claimed_periods(&self, id_campaign: IdCampaign) -> u64 {
+        self.subscription_claimed.get(&id_campaign).unwrap_or(0)
+    }
+}
@@ -0,0 +1,221 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::UnorderedSet,
+    json_types::{Base64VecU8, U128},
+    serde::{Deserialize, Serialize},
+    AccountId, PromiseOrValue,
+};
+
+use crate::*;
+
+pub type TokenId = String;
+
+const ERR_SOULBOUND: &str = "Supporter badges are soulbound and can't be transferred";
+// mac dinh donate tu 1 NEAR tro len moi nhan badge
+pub(crate) const DEFAULT_BADGE_MIN_DONATION: u128 = 10u128.pow(24);
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SupporterBadge {
+    pub owner_id: AccountId,
+    pub metadata: TokenMetadata,
+}
+
+// Token theo chuan NEP-171
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Token {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub metadata: Option<TokenMetadata>,
+}
+
+// metadata cua contract theo chuan NEP-177
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NFTContractMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl Contract {
+    /// Mint badge cho donor khi tong so tien donate (chua nhan he so early-bird) dat nguong, moi donor 1 badge / campaign
+    pub(crate) fn internal_mint_supporter_badge(
+        &mut self,
+        campaign: &Campaign,
+        owner_id: &AccountId,
+        total_donated: u128,
+    ) {
+        if total_donated < self.badge_min_donation {
+            return;
+        }
+        let token_id = format!("{}:{}", campaign.id, owner_id);
+        if self.badges.get(&token_id).is_some() {
+            return;
+        }
+        let metadata = TokenMetadata {
+            title: Some(format!("Supporter of {}", campaign.name_campaign)),
+            description: Some(format!(
+                "Soulbound badge for supporters of campaign #{}",
+                campaign.id
+            )),
+            media: campaign.metadata.media.clone(),
+            issued_at: Some(env::block_timestamp_ms().to_string()),
+            ..Default::default()
+        };
+        self.badges.insert(
+            &token_id,
+            &SupporterBadge {
+                owner_id: owner_id.clone(),
+                metadata,
+            },
+        );
+        let mut tokens = self.badges_per_owner.get(owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::BadgesPerOwnerNested {
+                account_hash: env::sha256(owner_id.as_bytes()),
+            })
+        });
+        tokens.insert(&token_id);
+        self.badges_per_owner.insert(owner_id, &tokens);
+        Nep171Event::NftMint(vec![NftMintLog {
+            owner_id: owner_id.clone(),
+            token_ids: vec![token_id],
+        }])
+        .emit();
+    }
+
+    /// Thu hoi badge khi so tien donate con lai cua donor xuong duoi nguong
+    pub(crate) fn internal_burn_supporter_badge(
+        &mut self,
+        id_campaign: IdCampaign,
+        owner_id: &AccountId,
+        remaining: u128,
+    ) {
+        if remaining >= self.badge_min_donation {
+            return;
+        }
+        let token_id = format!("{}:{}", id_campaign, owner_id);
+        if self.badges.remove(&token_id).is_none() {
+            return;
+        }
+        if let Some(mut tokens) = self.badges_per_owner.get(owner_id) {
+            tokens.remove(&token_id);
+            if tokens.is_empty() {
+                self.badges_per_owner.remove(owner_id);
+            } else {
+                self.badges_per_owner.insert(owner_id, &tokens);
+            }
+        }
+        Nep171Event::NftBurn(vec![NftMintLog {
+            owner_id: owner_id.clone(),
+            token_ids: vec![token_id],
+        }])
+        .emit();
+    }
+
+    fn internal_badge_token(&self, token_id: TokenId) -> Option<Token> {
+        self.badges.get(&token_id).map(|badge| Token {
+            token_id,
+            owner_id: badge.owner_id,
+            metadata: Some(badge.metadata),
+        })
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_badge_min_donation(&mut self, min_donation: U128) {
+        self.assert_owner();
+        self.badge_min_donation = min_donation.0;
+    }
+
+    pub fn get_badge_min_donation(&self) -> U128 {
+        U128(self.badge_min_donation)
+    }
+
+    // NEP-171: badge khong the chuyen nhuong
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        panic!("{}", ERR_SOULBOUND);
+    }
+
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        panic!("{}", ERR_SOULBOUND);
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.internal_badge_token(token_id)
+    }
+
+    // NEP-177
+    pub fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: String::from("nft-1.0.0"),
+            name: String::from("Campaign Supporter Badge"),
+            symbol: String::from("SUPPORTER"),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    // NEP-181
+    pub fn nft_total_supply(&self) -> U128 {
+        U128(self.badges.len() as u128)
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        self.badges
+            .keys()
+            .skip(from_index.map_or(0, |i| i.0) as usize)
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .filter_map(|token_id| self.internal_badge_token(token_id))
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        U128(
+            self.badges_per_owner
+                .get(&account_id)
+                .map_or(0, |tokens| tokens.len()) as u128,
+        )
+    }
+
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        match self.badges_per_owner.get(&account_id) {
+            Some(tokens) => tokens
+                .iter()
+                .skip(from_index.map_or(0, |i| i.0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .filter_map(|token_id| self.internal_badge_token(token_id))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
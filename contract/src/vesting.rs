@@ -0,0 +1,172 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+use crate::*;
+
+const MAX_VESTING_DURATION_MS: u64 = 4 * 365 * 24 * 60 * 60 * 1000;
+
+// tien cua campaign thanh cong duoc tra dan cho beneficiary trong `duration` ms
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Vesting {
+    pub total: u128,
+    pub claimed: u128,
+    pub start: u64,
+    pub duration: u64,
+    // tong tien donor da donate, dung de chia phan chua vest khi bi halt
+    pub donated: u128,
+    // phan chua vest tra lai donor, > 0 khi campaign bi halt
+    pub refund_pool: u128,
+}
+
+impl Vesting {
+    pub fn vested(&self, now: u64) -> u128 {
+        if self.refund_pool > 0 {
+            return self.total;
+        }
+        let elapsed = std::cmp::min(now.saturating_sub(self.start), self.duration);
+        (U256::from(self.total) * U256::from(elapsed) / U256::from(self.duration)).as_u128()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingView {
+    pub total: U128,
+    pub vested: U128,
+    pub claimed: U128,
+    pub start: U64,
+    pub duration: U64,
+    pub halted: bool,
+}
+
+pub(crate) fn assert_valid_vesting_duration(duration: Option<u64>) {
+    if let Some(duration) = duration {
+        require!(
+            duration > 0 && duration <= MAX_VESTING_DURATION_MS,
+            "Vesting duration must be between 1 ms and 4 years"
+        );
+    }
+}
+
+impl Contract {
+    /// Tra tien cho beneficiary hoac chia theo recipients
+    pub(crate) fn internal_pay_beneficiaries(&self, campaign: &Campaign, amount: u128) {
+        if campaign.recipients.is_empty() {
+            self.internal_payout(
+                campaign.id,
+                campaign.beneficiary.clone(),
+                amount,
+                PayoutKind::Finish,
+            );
+        } else {
+            for (receiver, share) in split_payout(amount, &campaign.recipients) {
+                if share > 0 {
+                    self.internal_payout(campaign.id, receiver, share, PayoutKind::Finish);
+                }
+            }
+        }
+    }
+
+    /// So tien refund thuc te cua donor: campaign bi halt chi con phan chua vest
+    pub(crate) fn internal_refund_share(&self, id_campaign: IdCampaign, donated: u128) -> u128 {
+        match self.vestings.get(&id_campaign) {
+            Some(vesting) if vesting.refund_pool > 0 => (U256::from(donated)
+                * U256::from(vesting.refund_pool)
+                / U256::from(vesting.donated))
+            .as_u128(),
+            _ => donated,
+        }
+    }
+
+    /// Nguoc lai cua internal_refund_share, dung khi refund that bai
+    pub(crate) fn internal_unscale_refund(&self, id_campaign: IdCampaign, refunded: u128) -> u128 {
+        match self.vestings.get(&id_campaign) {
+            Some(vesting) if vesting.refund_pool > 0 => (U256::from(refunded)
+                * U256::from(vesting.donated)
+                / U256::from(vesting.refund_pool))
+            .as_u128(),
+            _ => refunded,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Beneficiary (hoac creator) rut phan da vest
+    pub fn withdraw_vested(&mut self, id_campaign: IdCampaign) -> U128 {
+        let campaign = self
+            .campaign
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign doesn't exsit"));
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == campaign.beneficiary || caller == campaign.creator,
+            "Just the creator or beneficiary can withdraw"
+        );
+        let mut vesting = self
+            .vestings
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign has no vesting"));
+        let amount = vesting.vested(env::block_timestamp_ms()) - vesting.claimed;
+        require!(amount > 0, "Nothing vested to withdraw");
+        vesting.claimed += amount;
+        self.vestings.insert(&id_campaign, &vesting);
+        self.internal_pay_beneficiaries(&campaign, amount);
+        U128(amount)
+    }
+
+    /// Moderator dung vesting, phan chua vest duoc tra lai donor qua refund / refund_batch
+    pub fn halt_campaign(&mut self, id_campaign: IdCampaign) {
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == self.owner_id || self.moderators.contains(&caller),
+            "Just a moderator can halt a campaign"
+        );
+        let mut vesting = self
+            .vestings
+            .get(&id_campaign)
+            .unwrap_or_else(|| env::panic_str("This campaign has no vesting"));
+        require!(vesting.refund_pool == 0, "This campaign was halted");
+        let vested = vesting.vested(env::block_timestamp_ms());
+        let remainder = vesting.total - vested;
+        require!(remainder > 0, "This campaign is fully vested");
+        require!(vesting.donated > 0, "This campaign has no donor to refund");
+        vesting.total = vested;
+        vesting.refund_pool = remainder;
+        self.vestings.insert(&id_campaign, &vesting);
+        let mut campaign = self.campaign.get(&id_campaign).unwrap();
+        campaign.amount = remainder;
+        campaign.refund = true;
+        self.campaign.insert(&id_campaign, &campaign);
+        self.refund_cursor.remove(&id_campaign);
+    }
+
+    pub fn get_vesting(&self, id_campaign: IdCampaign) -> Option<VestingView> {
+        self.vestings.get(&id_campaign).map(|vesting| VestingView {
+            total: U128(vesting.total),
+            vested: U128(vesting.vested(env::block_timestamp_ms())),
+            claimed: U128(vesting.claimed),
+            start: U64(vesting.start),
+            duration: U64(vesting.duration),
+            halted: vesting.refund_pool > 0,
+        })
+    }
+
+    pub fn add_moderator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.moderators.insert(&account_id);
+    }
+
+    pub fn remove_moderator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.moderators.remove(&account_id);
+    }
+
+    pub fn get_moderators(&self) -> Vec<AccountId> {
+        self.moderators.to_vec()
+    }
+}